@@ -6,12 +6,18 @@
 use crate::config::PbsConfig;
 use crate::error::{PbsError, Result};
 use reqwest::Client;
+use retry::CircuitBreaker;
 use serde::de::DeserializeOwned;
 use serde::Deserialize;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 use tracing::{debug, warn};
 
+mod api_metrics;
+mod retry;
 mod types;
+pub use api_metrics::ApiMetrics;
 pub use types::*;
 
 /// PBS API client.
@@ -20,6 +26,11 @@ pub struct PbsClient {
     client: Client,
     config: PbsConfig,
     auth_header: String,
+    /// Bounds the number of API requests in flight at once.
+    request_permits: Arc<Semaphore>,
+    api_metrics: ApiMetrics,
+    /// Shared by clones so every request sees the same PBS health.
+    circuit_breaker: Arc<CircuitBreaker>,
 }
 
 impl PbsClient {
@@ -35,14 +46,7 @@ impl PbsClient {
     /// use pbs_exporter::client::PbsClient;
     /// use pbs_exporter::config::PbsConfig;
     ///
-    /// let config = PbsConfig {
-    ///     endpoint: "https://pbs.example.com:8007".to_string(),
-    ///     token_id: "user@pam!token".to_string(),
-    ///     token_secret: "secret".to_string(),
-    ///     verify_tls: false,
-    ///     timeout_seconds: 5,
-    ///     snapshot_history_limit: 0,
-    /// };
+    /// let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// let client = PbsClient::new(config).unwrap();
     /// ```
     pub fn new(config: PbsConfig) -> Result<Self> {
@@ -52,16 +56,31 @@ impl PbsClient {
             .build()?;
 
         let auth_header = format!("PBSAPIToken={}:{}", config.token_id, config.token_secret);
+        let request_permits = Arc::new(Semaphore::new(config.max_concurrent_requests.max(1)));
+        let api_metrics = ApiMetrics::new()?;
+        let circuit_breaker = Arc::new(CircuitBreaker::new(
+            config.circuit_breaker.clone(),
+            api_metrics.circuit_breaker_state.clone(),
+        ));
 
         Ok(Self {
             client,
             config,
             auth_header,
+            request_permits,
+            api_metrics,
+            circuit_breaker,
         })
     }
 
+    /// Per-endpoint request counters and latency histograms for this client.
+    pub fn api_metrics(&self) -> &ApiMetrics {
+        &self.api_metrics
+    }
+
     async fn get_api_data<T>(
         &self,
+        endpoint: &str,
         path: &str,
         request_description: &str,
         parse_error_context: Option<String>,
@@ -70,11 +89,96 @@ impl PbsClient {
         T: DeserializeOwned,
     {
         let url = format!("{}{}", self.config.endpoint, path);
+
+        let Some(permit) = self.circuit_breaker.allow() else {
+            self.api_metrics.observe_rejected(endpoint);
+            debug!("Skipping {}: circuit breaker open", request_description);
+            return Err(PbsError::CircuitOpen);
+        };
+        // Records a failure if the half-open trial is dropped mid-request, e.g.
+        // when a scrape times out.
+        let mut outcome = self.circuit_breaker.guard(permit);
+
+        let mut retry = 0;
+        let result = loop {
+            let result = self
+                .fetch_once(
+                    endpoint,
+                    &url,
+                    request_description,
+                    parse_error_context.clone(),
+                )
+                .await;
+            match &result {
+                Err(e) if retry < self.config.retry.max_retries && retry::is_retryable(e) => {
+                    let delay = retry::backoff(&self.config.retry, retry);
+                    warn!(
+                        "Retrying {} in {:?} after error: {}",
+                        request_description, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+
+                    // Stop if concurrent requests opened the breaker meanwhile or this
+                    // is the half-open trial; the failure is still recorded below so a
+                    // failed trial re-opens the breaker.
+                    let Some(permit) = self.circuit_breaker.allow() else {
+                        self.api_metrics.observe_rejected(endpoint);
+                        debug!("Not retrying {}: circuit breaker open", request_description);
+                        break result;
+                    };
+                    outcome.renew(permit);
+                    self.api_metrics.observe_retry(endpoint);
+                    retry += 1;
+                }
+                _ => break result,
+            }
+        };
+
+        outcome.record(matches!(&result, Err(e) if retry::is_retryable(e)));
+        result
+    }
+
+    /// Make a single request attempt, recording it in the API metrics.
+    async fn fetch_once<T>(
+        &self,
+        endpoint: &str,
+        url: &str,
+        request_description: &str,
+        parse_error_context: Option<String>,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        // Hold a permit until the body has been read so the limit covers the whole
+        // request; it is released while waiting to retry.
+        let _permit = self
+            .request_permits
+            .acquire()
+            .await
+            .map_err(|e| PbsError::Other(e.to_string()))?;
         debug!("Fetching {} from: {}", request_description, url);
 
+        let start = Instant::now();
+        let result = self
+            .fetch(url, request_description, parse_error_context)
+            .await;
+        self.api_metrics.observe(endpoint, &result, start.elapsed());
+
+        result
+    }
+
+    async fn fetch<T>(
+        &self,
+        url: &str,
+        request_description: &str,
+        parse_error_context: Option<String>,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
         let response = self
             .client
-            .get(&url)
+            .get(url)
             .header("Authorization", &self.auth_header)
             .send()
             .await?;
@@ -99,11 +203,41 @@ impl PbsClient {
         Ok(api_response.data)
     }
 
+    /// List the nodes known to PBS.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn get_nodes(&self) -> Result<Vec<NodeInfo>> {
+        self.get_api_data("nodes", "/api2/json/nodes", "nodes", None)
+            .await
+    }
+
+    /// Node names to collect host and task metrics from.
+    ///
+    /// Returns the configured `nodes`, or the nodes reported by
+    /// [`get_nodes`](Self::get_nodes) when `discover_nodes` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if node discovery fails.
+    pub async fn nodes(&self) -> Result<Vec<String>> {
+        if !self.config.discover_nodes {
+            return Ok(self.config.nodes.clone());
+        }
+        let nodes = self.get_nodes().await?;
+        Ok(nodes.into_iter().map(|n| n.node).collect())
+    }
+
     /// Get node status (CPU, memory, disk, etc.).
     ///
     /// Fetches the current status of the PBS node including CPU usage, memory,
     /// swap, disk usage, load averages, and uptime.
     ///
+    /// # Arguments
+    ///
+    /// * `node` - Node name (`localhost` addresses whichever node answers)
+    ///
     /// # Returns
     ///
     /// Returns a `NodeStatus` struct containing all node metrics, or an error if
@@ -122,23 +256,17 @@ impl PbsClient {
     /// # use pbs_exporter::client::PbsClient;
     /// # use pbs_exporter::config::PbsConfig;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: false,
-    /// #     timeout_seconds: 5,
-    /// #     snapshot_history_limit: 0,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
-    /// let status = client.get_node_status().await?;
+    /// let status = client.get_node_status("localhost").await?;
     /// println!("CPU usage: {}", status.cpu);
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_node_status(&self) -> Result<NodeStatus> {
+    pub async fn get_node_status(&self, node: &str) -> Result<NodeStatus> {
         self.get_api_data(
-            "/api2/json/nodes/localhost/status",
+            "node_status",
+            &format!("/api2/json/nodes/{}/status", percent_encode(node, b"")),
             "node status",
             Some("Failed to parse node status".to_string()),
         )
@@ -148,7 +276,8 @@ impl PbsClient {
     /// Get datastore usage information.
     ///
     /// Fetches usage statistics for all configured datastores including total,
-    /// used, and available bytes.
+    /// used, and available bytes, PBS's estimated full date and the usage
+    /// history it is based on.
     ///
     /// # Returns
     ///
@@ -165,14 +294,7 @@ impl PbsClient {
     /// # use pbs_exporter::client::PbsClient;
     /// # use pbs_exporter::config::PbsConfig;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: false,
-    /// #     timeout_seconds: 5,
-    /// #     snapshot_history_limit: 0,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
     /// let datastores = client.get_datastore_usage().await?;
     /// for ds in datastores {
@@ -182,8 +304,13 @@ impl PbsClient {
     /// # }
     /// ```
     pub async fn get_datastore_usage(&self) -> Result<Vec<DatastoreUsage>> {
-        self.get_api_data("/api2/json/status/datastore-usage", "datastore usage", None)
-            .await
+        self.get_api_data(
+            "datastore_usage",
+            "/api2/json/status/datastore-usage",
+            "datastore usage",
+            None,
+        )
+        .await
     }
 
     /// Get backup groups for a specific datastore.
@@ -213,14 +340,7 @@ impl PbsClient {
     /// # use pbs_exporter::client::PbsClient;
     /// # use pbs_exporter::config::PbsConfig;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: false,
-    /// #     timeout_seconds: 5,
-    /// #     snapshot_history_limit: 0,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
     /// let groups = client.get_backup_groups("backup").await?;
     /// for group in groups {
@@ -230,14 +350,91 @@ impl PbsClient {
     /// # }
     /// ```
     pub async fn get_backup_groups(&self, datastore: &str) -> Result<Vec<BackupGroup>> {
+        self.get_backup_groups_in(datastore, "").await
+    }
+
+    /// Get the backup groups of one namespace (not its children).
+    ///
+    /// Each returned group has its `ns` set to `namespace` (`None` for the
+    /// root namespace, passed as an empty string).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the datastore or namespace is not accessible, the
+    /// API request fails or the response cannot be parsed.
+    pub async fn get_backup_groups_in(
+        &self,
+        datastore: &str,
+        namespace: &str,
+    ) -> Result<Vec<BackupGroup>> {
+        let description = describe_namespace(datastore, namespace);
+        let mut groups: Vec<BackupGroup> = self
+            .get_api_data(
+                "groups",
+                &format!(
+                    "/api2/json/admin/datastore/{}/groups{}",
+                    datastore,
+                    namespace_query(namespace)
+                ),
+                &format!("backup groups for {}", description),
+                Some(format!("Failed to parse backup groups for {}", description)),
+            )
+            .await?;
+
+        let ns = (!namespace.is_empty()).then(|| namespace.to_string());
+        for group in &mut groups {
+            group.ns.clone_from(&ns);
+        }
+        Ok(groups)
+    }
+
+    /// List the namespaces of a datastore, including the root namespace.
+    ///
+    /// # Arguments
+    ///
+    /// * `datastore` - Name of the datastore to query
+    /// * `max_depth` - Namespace levels below the root to descend
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the datastore is not accessible, the API request
+    /// fails or the response cannot be parsed.
+    pub async fn get_namespaces(
+        &self,
+        datastore: &str,
+        max_depth: usize,
+    ) -> Result<Vec<NamespaceInfo>> {
         self.get_api_data(
-            &format!("/api2/json/admin/datastore/{}/groups", datastore),
-            &format!("backup groups for {}", datastore),
-            Some(format!("Failed to parse backup groups for {}", datastore)),
+            "namespaces",
+            &format!(
+                "/api2/json/admin/datastore/{}/namespace?max-depth={}",
+                datastore, max_depth
+            ),
+            &format!("namespaces for {}", datastore),
+            None,
         )
         .await
     }
 
+    /// Namespaces to collect snapshots and groups from, root first.
+    ///
+    /// Only the root namespace (`""`) is returned without querying PBS when
+    /// `namespace_max_depth` is 0; otherwise namespaces are discovered with
+    /// [`get_namespaces`](Self::get_namespaces) down to that depth.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if namespace discovery fails.
+    pub async fn namespaces(&self, datastore: &str) -> Result<Vec<String>> {
+        if self.config.namespace_max_depth == 0 {
+            return Ok(vec![String::new()]);
+        }
+        let namespaces = self
+            .get_namespaces(datastore, self.config.namespace_max_depth)
+            .await?;
+        Ok(namespaces.into_iter().map(|n| n.ns).collect())
+    }
+
     /// Get PBS version information.
     ///
     /// Fetches the current version, release, and repository ID of the
@@ -261,14 +458,7 @@ impl PbsClient {
     /// # use pbs_exporter::client::PbsClient;
     /// # use pbs_exporter::config::PbsConfig;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: false,
-    /// #     timeout_seconds: 5,
-    /// #     snapshot_history_limit: 0,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
     /// let version = client.get_version().await?;
     /// println!("PBS version: {} ({})", version.version, version.release);
@@ -276,11 +466,46 @@ impl PbsClient {
     /// # }
     /// ```
     pub async fn get_version(&self) -> Result<VersionInfo> {
-        self.get_api_data("/api2/json/version", "version", None)
+        self.get_api_data("version", "/api2/json/version", "version", None)
             .await
     }
 }
 
+/// `?ns=` query selecting a namespace; empty for the root namespace.
+///
+/// The `/` level separator is kept as it is.
+fn namespace_query(namespace: &str) -> String {
+    if namespace.is_empty() {
+        return String::new();
+    }
+
+    format!("?ns={}", percent_encode(namespace, b"/"))
+}
+
+/// Percent-encode `value`, keeping only unreserved characters and `keep` as they are.
+fn percent_encode(value: &str, keep: &[u8]) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(char::from(byte))
+            }
+            _ if keep.contains(&byte) => encoded.push(char::from(byte)),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Datastore and namespace for log and error messages.
+fn describe_namespace(datastore: &str, namespace: &str) -> String {
+    if namespace.is_empty() {
+        datastore.to_string()
+    } else {
+        format!("{} namespace {}", datastore, namespace)
+    }
+}
+
 /// Generic PBS API response wrapper.
 #[derive(Debug, Deserialize)]
 struct ApiResponse<T> {
@@ -317,14 +542,7 @@ impl PbsClient {
     /// # use pbs_exporter::client::PbsClient;
     /// # use pbs_exporter::config::PbsConfig;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: false,
-    /// #     timeout_seconds: 5,
-    /// #     snapshot_history_limit: 0,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
     /// let snapshots = client.get_snapshots("backup").await?;
     /// for snapshot in snapshots {
@@ -337,15 +555,43 @@ impl PbsClient {
     /// # }
     /// ```
     pub async fn get_snapshots(&self, datastore: &str) -> Result<Vec<Snapshot>> {
-        let snapshots: Vec<Snapshot> = self
+        self.get_snapshots_in(datastore, "").await
+    }
+
+    /// Get the snapshots of one namespace (not its children).
+    ///
+    /// Each returned snapshot has its `ns` set to `namespace` (`None` for the
+    /// root namespace, passed as an empty string).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the datastore or namespace is not accessible, the
+    /// API request fails or the response cannot be parsed.
+    pub async fn get_snapshots_in(
+        &self,
+        datastore: &str,
+        namespace: &str,
+    ) -> Result<Vec<Snapshot>> {
+        let description = describe_namespace(datastore, namespace);
+        let mut snapshots: Vec<Snapshot> = self
             .get_api_data(
-                &format!("/api2/json/admin/datastore/{}/snapshots", datastore),
-                &format!("snapshots for {}", datastore),
-                Some(format!("Failed to parse snapshots for {}", datastore)),
+                "snapshots",
+                &format!(
+                    "/api2/json/admin/datastore/{}/snapshots{}",
+                    datastore,
+                    namespace_query(namespace)
+                ),
+                &format!("snapshots for {}", description),
+                Some(format!("Failed to parse snapshots for {}", description)),
             )
             .await?;
 
-        debug!("Fetched {} snapshots for {}", snapshots.len(), datastore);
+        let ns = (!namespace.is_empty()).then(|| namespace.to_string());
+        for snapshot in &mut snapshots {
+            snapshot.ns.clone_from(&ns);
+        }
+
+        debug!("Fetched {} snapshots for {}", snapshots.len(), description);
         Ok(snapshots)
     }
 }
@@ -359,6 +605,7 @@ impl PbsClient {
     ///
     /// # Arguments
     ///
+    /// * `node` - Node name to list tasks for
     /// * `limit` - Optional maximum number of tasks to return (default: 50)
     ///
     /// # Returns
@@ -379,16 +626,9 @@ impl PbsClient {
     /// # use pbs_exporter::client::PbsClient;
     /// # use pbs_exporter::config::PbsConfig;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: false,
-    /// #     timeout_seconds: 5,
-    /// #     snapshot_history_limit: 0,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
-    /// let tasks = client.get_tasks(Some(10)).await?;
+    /// let tasks = client.get_tasks("localhost", Some(10)).await?;
     /// for task in tasks {
     ///     println!("Task: {} ({})", task.worker_type, task.upid);
     ///     if let Some(status) = task.status {
@@ -398,10 +638,15 @@ impl PbsClient {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn get_tasks(&self, limit: Option<u64>) -> Result<Vec<Task>> {
+    pub async fn get_tasks(&self, node: &str, limit: Option<u64>) -> Result<Vec<Task>> {
         let limit_param = limit.unwrap_or(50);
         self.get_api_data(
-            &format!("/api2/json/nodes/localhost/tasks?limit={}", limit_param),
+            "tasks",
+            &format!(
+                "/api2/json/nodes/{}/tasks?limit={}",
+                percent_encode(node, b""),
+                limit_param
+            ),
             "tasks",
             None,
         )
@@ -436,14 +681,7 @@ impl PbsClient {
     /// # use pbs_exporter::client::PbsClient;
     /// # use pbs_exporter::config::PbsConfig;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: false,
-    /// #     timeout_seconds: 5,
-    /// #     snapshot_history_limit: 0,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
     /// let gc_status = client.get_gc_status("backup").await?;
     /// if let Some(pending) = gc_status.pending_bytes {
@@ -457,6 +695,7 @@ impl PbsClient {
     /// ```
     pub async fn get_gc_status(&self, datastore: &str) -> Result<GcStatus> {
         self.get_api_data(
+            "gc",
             &format!("/api2/json/admin/datastore/{}/gc", datastore),
             &format!("GC status for {}", datastore),
             None,
@@ -464,6 +703,49 @@ impl PbsClient {
         .await
     }
 
+    /// Get the detailed status of a datastore.
+    ///
+    /// Queries `/admin/datastore/{store}/status` in verbose mode, which adds
+    /// group and snapshot counts per backup type and the embedded GC status to
+    /// the capacity figures.
+    ///
+    /// # Arguments
+    ///
+    /// * `datastore` - Name of the datastore to query
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the datastore is not accessible, the API request
+    /// fails or the response cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use pbs_exporter::client::PbsClient;
+    /// # use pbs_exporter::config::PbsConfig;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
+    /// # let client = PbsClient::new(config)?;
+    /// let status = client.get_datastore_status("backup").await?;
+    /// for (backup_type, counts) in status.counts.unwrap_or_default() {
+    ///     println!("{}: {} groups, {} snapshots", backup_type, counts.groups, counts.snapshots);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_datastore_status(&self, datastore: &str) -> Result<DatastoreStatus> {
+        self.get_api_data(
+            "datastore_status",
+            &format!(
+                "/api2/json/admin/datastore/{}/status?verbose=true",
+                datastore
+            ),
+            &format!("datastore status for {}", datastore),
+            None,
+        )
+        .await
+    }
+
     /// Get configured tape drives.
     ///
     /// Fetches information about all configured tape drives in the PBS system
@@ -488,14 +770,7 @@ impl PbsClient {
     /// # use pbs_exporter::client::PbsClient;
     /// # use pbs_exporter::config::PbsConfig;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: false,
-    /// #     timeout_seconds: 5,
-    /// #     snapshot_history_limit: 0,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
     /// let drives = client.get_tape_drives().await?;
     /// for drive in drives {
@@ -508,7 +783,177 @@ impl PbsClient {
     /// # }
     /// ```
     pub async fn get_tape_drives(&self) -> Result<Vec<TapeDrive>> {
-        self.get_api_data("/api2/json/tape/drive", "tape drives", None)
+        self.get_api_data("tape_drives", "/api2/json/tape/drive", "tape drives", None)
+            .await
+    }
+
+    /// Get all sync jobs with their last and next run.
+    ///
+    /// Queries `/admin/sync`, which returns each job's configuration together
+    /// with the state of its last run and its next scheduled run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use pbs_exporter::client::PbsClient;
+    /// # use pbs_exporter::config::PbsConfig;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
+    /// # let client = PbsClient::new(config)?;
+    /// for job in client.get_sync_jobs().await? {
+    ///     println!("{}: {:?}", job.id, job.status.last_run_state);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_sync_jobs(&self) -> Result<Vec<SyncJob>> {
+        self.get_api_data("sync_jobs", "/api2/json/admin/sync", "sync jobs", None)
             .await
     }
+
+    /// Get all verification jobs with their last and next run.
+    ///
+    /// Queries `/admin/verify`, which returns each job's configuration
+    /// (including `outdated-after` and `ignore-verified`) together with the
+    /// state of its last run and its next scheduled run.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn get_verification_jobs(&self) -> Result<Vec<VerificationJob>> {
+        self.get_api_data(
+            "verify_jobs",
+            "/api2/json/admin/verify",
+            "verification jobs",
+            None,
+        )
+        .await
+    }
+
+    /// Get all prune jobs with their retention settings and last and next run.
+    ///
+    /// Queries `/admin/prune` (PBS 3 and later).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn get_prune_jobs(&self) -> Result<Vec<PruneJob>> {
+        self.get_api_data("prune_jobs", "/api2/json/admin/prune", "prune jobs", None)
+            .await
+    }
+
+    /// Get all tape backup jobs with their last and next run.
+    ///
+    /// Queries `/tape/backup`, which combines the `/config/tape-backup-job`
+    /// configuration with each job's schedule state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn get_tape_backup_jobs(&self) -> Result<Vec<TapeBackupJob>> {
+        self.get_api_data(
+            "tape_backup_jobs",
+            "/api2/json/tape/backup",
+            "tape backup jobs",
+            None,
+        )
+        .await
+    }
+
+    /// Get the configured media pools.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn get_media_pools(&self) -> Result<Vec<MediaPool>> {
+        self.get_api_data(
+            "media_pools",
+            "/api2/json/config/media-pool",
+            "media pools",
+            None,
+        )
+        .await
+    }
+
+    /// Get the tape media inventory.
+    ///
+    /// Queries `/tape/media/list` without `update-status`, so listing the
+    /// inventory never touches the tape library; online/offline locations are
+    /// as current as PBS's last inventory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn get_tape_media(&self) -> Result<Vec<TapeMedia>> {
+        self.get_api_data(
+            "tape_media",
+            "/api2/json/tape/media/list?update-status=false",
+            "tape media",
+            None,
+        )
+        .await
+    }
+
+    /// Get the live status of a tape drive and its loaded medium.
+    ///
+    /// The drive is accessed directly, so this fails while a task holds the
+    /// drive; check [`TapeDrive::state`] first.
+    ///
+    /// # Arguments
+    ///
+    /// * `drive` - Name of the drive to query
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the drive is busy, the API request fails or the
+    /// response cannot be parsed.
+    pub async fn get_tape_drive_status(&self, drive: &str) -> Result<TapeDriveStatus> {
+        self.get_api_data(
+            "tape_drive_status",
+            &format!("/api2/json/tape/drive/{}/status", drive),
+            &format!("tape drive status for {}", drive),
+            None,
+        )
+        .await
+    }
+
+    /// Get configured tape changers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn get_tape_changers(&self) -> Result<Vec<TapeChanger>> {
+        self.get_api_data(
+            "tape_changers",
+            "/api2/json/tape/changer",
+            "tape changers",
+            None,
+        )
+        .await
+    }
+
+    /// Get the drives and slots of a tape changer with the media they hold.
+    ///
+    /// PBS answers from its cached changer status where possible.
+    ///
+    /// # Arguments
+    ///
+    /// * `changer` - Name of the changer to query
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn get_tape_changer_status(&self, changer: &str) -> Result<Vec<ChangerEntry>> {
+        self.get_api_data(
+            "tape_changer_status",
+            &format!("/api2/json/tape/changer/{}/status", changer),
+            &format!("tape changer status for {}", changer),
+            None,
+        )
+        .await
+    }
 }
@@ -0,0 +1,104 @@
+//! Per-endpoint accounting of PBS API requests.
+
+use crate::error::{PbsError, Result};
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts};
+use std::time::Duration;
+
+/// Latency buckets (seconds) sized around the default 5s request timeout.
+const DURATION_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Prometheus instruments recording every request made by a [`PbsClient`](super::PbsClient).
+///
+/// The instruments are owned by the client and registered into a metric
+/// registry by the metrics collector, so counts survive registry resets.
+#[derive(Clone)]
+pub struct ApiMetrics {
+    pub(crate) calls_total: IntCounterVec,
+    pub(crate) request_duration_seconds: HistogramVec,
+    pub(crate) retries_total: IntCounterVec,
+    pub(crate) circuit_breaker_state: IntGauge,
+}
+
+impl ApiMetrics {
+    pub(crate) fn new() -> Result<Self> {
+        let calls_total = IntCounterVec::new(
+            Opts::new(
+                "pbs_exporter_api_calls_total",
+                "Total number of API calls made to PBS by logical endpoint and outcome",
+            ),
+            &["endpoint", "outcome"],
+        )
+        .map_err(|e| PbsError::Metrics(e.to_string()))?;
+
+        let request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "pbs_exporter_api_request_duration_seconds",
+                "Duration of PBS API requests in seconds by logical endpoint",
+            )
+            .buckets(DURATION_BUCKETS.to_vec()),
+            &["endpoint"],
+        )
+        .map_err(|e| PbsError::Metrics(e.to_string()))?;
+
+        let retries_total = IntCounterVec::new(
+            Opts::new(
+                "pbs_exporter_api_retries_total",
+                "Total number of retried PBS API requests by logical endpoint",
+            ),
+            &["endpoint"],
+        )
+        .map_err(|e| PbsError::Metrics(e.to_string()))?;
+
+        let circuit_breaker_state = IntGauge::new(
+            "pbs_exporter_circuit_breaker_state",
+            "State of the circuit breaker for PBS requests (0 = closed, 1 = open, 2 = half-open)",
+        )
+        .map_err(|e| PbsError::Metrics(e.to_string()))?;
+
+        Ok(Self {
+            calls_total,
+            request_duration_seconds,
+            retries_total,
+            circuit_breaker_state,
+        })
+    }
+
+    /// Record one finished request.
+    pub(crate) fn observe<T>(&self, endpoint: &str, result: &Result<T>, duration: Duration) {
+        self.calls_total
+            .with_label_values(&[endpoint, outcome(result)])
+            .inc();
+        self.request_duration_seconds
+            .with_label_values(&[endpoint])
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Record a request rejected by the open circuit breaker.
+    pub(crate) fn observe_rejected(&self, endpoint: &str) {
+        self.calls_total
+            .with_label_values(&[endpoint, "circuit_open"])
+            .inc();
+    }
+
+    /// Record a retry of a failed request.
+    pub(crate) fn observe_retry(&self, endpoint: &str) {
+        self.retries_total.with_label_values(&[endpoint]).inc();
+    }
+}
+
+/// Classify a request result into an outcome label.
+fn outcome<T>(result: &Result<T>) -> &'static str {
+    match result {
+        Ok(_) => "success",
+        Err(PbsError::Api(e)) => match e.status() {
+            Some(status) if status.is_client_error() => "http_4xx",
+            Some(status) if status.is_server_error() => "http_5xx",
+            Some(_) => "http_other",
+            None if e.is_timeout() => "timeout",
+            None if e.is_decode() => "parse_error",
+            None => "network_error",
+        },
+        Err(PbsError::ParseError(_)) => "parse_error",
+        Err(_) => "network_error",
+    }
+}
@@ -0,0 +1,332 @@
+//! Retry backoff, error classification and circuit breaking for PBS requests.
+
+use crate::config::{CircuitBreakerConfig, RetryConfig};
+use crate::error::PbsError;
+use prometheus::IntGauge;
+use reqwest::StatusCode;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Whether a failed request may succeed if repeated.
+///
+/// Connection errors, timeouts and the HTTP statuses PBS returns while it is
+/// restarting or overloaded are transient; authentication, permission, missing
+/// resources and malformed responses are not.
+pub(crate) fn is_retryable(error: &PbsError) -> bool {
+    match error {
+        PbsError::Api(e) => match e.status() {
+            Some(status) => matches!(
+                status,
+                StatusCode::TOO_MANY_REQUESTS
+                    | StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            None => !e.is_decode() && !e.is_builder(),
+        },
+        _ => false,
+    }
+}
+
+/// Backoff before retry number `retry` (0-based).
+///
+/// The delay doubles with every retry up to `max_backoff_ms`; half of it is
+/// randomized so concurrent requests do not retry in lockstep.
+pub(crate) fn backoff(config: &RetryConfig, retry: u32) -> Duration {
+    let ceiling = config
+        .initial_backoff_ms
+        .saturating_mul(1u64 << retry.min(32))
+        .min(config.max_backoff_ms);
+    let half = ceiling / 2;
+    let jitter = if half == 0 { 0 } else { random() % (half + 1) };
+    Duration::from_millis(ceiling - half + jitter)
+}
+
+/// Cheap random number without an extra dependency; `RandomState` is seeded
+/// randomly per thread and advanced for every instance.
+fn random() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    hasher.finish()
+}
+
+/// State of a [`CircuitBreaker`], exposed as `pbs_exporter_circuit_breaker_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+impl State {
+    fn metric_value(self) -> i64 {
+        match self {
+            State::Closed => 0,
+            State::Open => 1,
+            State::HalfOpen => 2,
+        }
+    }
+}
+
+struct BreakerState {
+    state: State,
+    consecutive_failures: u32,
+    /// When the breaker last opened or let a half-open trial through.
+    since: Option<Instant>,
+}
+
+/// Stops sending requests to a PBS that keeps failing with transient errors.
+///
+/// Closed: requests pass and consecutive failures are counted. Open: requests
+/// are rejected until `open_seconds` have passed. Half-open: one trial request
+/// is let through; its outcome closes or re-opens the breaker. A trial without
+/// an outcome after another `open_seconds` is given up and a new one allowed.
+pub(crate) struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    inner: Mutex<BreakerState>,
+    state_gauge: IntGauge,
+}
+
+impl CircuitBreaker {
+    pub(crate) fn new(config: CircuitBreakerConfig, state_gauge: IntGauge) -> Self {
+        state_gauge.set(State::Closed.metric_value());
+        Self {
+            config,
+            inner: Mutex::new(BreakerState {
+                state: State::Closed,
+                consecutive_failures: 0,
+                since: None,
+            }),
+            state_gauge,
+        }
+    }
+
+    /// Whether a request may be sent now, and if so whether it is the
+    /// half-open trial.
+    pub(crate) fn allow(&self) -> Option<Permit> {
+        if self.config.failure_threshold == 0 {
+            return Some(Permit::Normal);
+        }
+
+        let mut inner = self.lock();
+        if inner.state == State::Closed {
+            return Some(Permit::Normal);
+        }
+
+        let cooled_down = inner
+            .since
+            .is_none_or(|since| since.elapsed() >= Duration::from_secs(self.config.open_seconds));
+        if cooled_down {
+            inner.since = Some(Instant::now());
+            self.transition(&mut inner, State::HalfOpen);
+        }
+        cooled_down.then_some(Permit::Trial)
+    }
+
+    /// Outcome recorder for a request allowed with `permit`.
+    ///
+    /// A trial guard dropped without an outcome records a failure, so a
+    /// cancelled trial re-opens the breaker instead of leaving it half-open.
+    /// Other cancelled requests record nothing.
+    pub(crate) fn guard(&self, permit: Permit) -> RequestGuard<'_> {
+        RequestGuard {
+            breaker: self,
+            permit,
+            recorded: false,
+        }
+    }
+
+    /// Record the outcome of an allowed request (after its retries).
+    pub(crate) fn record(&self, transient_failure: bool) {
+        if self.config.failure_threshold == 0 {
+            return;
+        }
+
+        let mut inner = self.lock();
+        if !transient_failure {
+            inner.consecutive_failures = 0;
+            self.transition(&mut inner, State::Closed);
+            return;
+        }
+
+        inner.consecutive_failures = inner.consecutive_failures.saturating_add(1);
+        if inner.state == State::HalfOpen
+            || inner.consecutive_failures >= self.config.failure_threshold
+        {
+            inner.since = Some(Instant::now());
+            self.transition(&mut inner, State::Open);
+        }
+    }
+
+    fn transition(&self, inner: &mut BreakerState, state: State) {
+        if inner.state != state {
+            inner.state = state;
+            self.state_gauge.set(state.metric_value());
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Why [`CircuitBreaker::allow`] let a request through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Permit {
+    /// The breaker is closed (or disabled).
+    Normal,
+    /// The single trial request of a half-open breaker.
+    Trial,
+}
+
+/// Outcome recorder for one allowed request, see [`CircuitBreaker::guard`].
+pub(crate) struct RequestGuard<'a> {
+    breaker: &'a CircuitBreaker,
+    permit: Permit,
+    recorded: bool,
+}
+
+impl RequestGuard<'_> {
+    /// Note the permit of a retry; a retry that became the trial makes the
+    /// whole request one.
+    pub(crate) fn renew(&mut self, permit: Permit) {
+        if permit == Permit::Trial {
+            self.permit = Permit::Trial;
+        }
+    }
+
+    /// Record the outcome of the request (after its retries).
+    pub(crate) fn record(mut self, transient_failure: bool) {
+        self.recorded = true;
+        self.breaker.record(transient_failure);
+    }
+}
+
+impl Drop for RequestGuard<'_> {
+    fn drop(&mut self) {
+        if !self.recorded && self.permit == Permit::Trial {
+            self.breaker.record(true);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(failure_threshold: u32, open_seconds: u64) -> CircuitBreaker {
+        let gauge = IntGauge::new("test_breaker_state", "test").unwrap();
+        CircuitBreaker::new(
+            CircuitBreakerConfig {
+                failure_threshold,
+                open_seconds,
+            },
+            gauge,
+        )
+    }
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let config = RetryConfig {
+            max_retries: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+        };
+        for _ in 0..20 {
+            let first = backoff(&config, 0);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+            let third = backoff(&config, 2);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+            let capped = backoff(&config, 30);
+            assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn test_breaker_opens_after_threshold() {
+        let breaker = breaker(2, 3600);
+        assert!(breaker.allow().is_some());
+        breaker.record(true);
+        assert!(breaker.allow().is_some());
+        breaker.record(true);
+        assert!(breaker.allow().is_none());
+        assert_eq!(breaker.state_gauge.get(), 1);
+    }
+
+    /// Pretend `open_seconds` have passed since the last state change.
+    fn expire(breaker: &CircuitBreaker) {
+        breaker.lock().since = Some(Instant::now() - Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_breaker_half_open_trial_decides_state() {
+        let breaker = breaker(1, 3600);
+        breaker.record(true);
+
+        // Cooldown elapsed: exactly one trial request passes
+        expire(&breaker);
+        assert!(breaker.allow().is_some());
+        assert!(breaker.allow().is_none());
+        assert_eq!(breaker.state_gauge.get(), 2);
+
+        breaker.record(true);
+        assert_eq!(breaker.state_gauge.get(), 1);
+
+        expire(&breaker);
+        assert!(breaker.allow().is_some());
+        breaker.record(false);
+        assert_eq!(breaker.state_gauge.get(), 0);
+        assert!(breaker.allow().is_some());
+    }
+
+    #[test]
+    fn test_breaker_gives_up_on_a_stuck_trial() {
+        let breaker = breaker(1, 3600);
+        breaker.record(true);
+        expire(&breaker);
+        assert!(breaker.allow().is_some());
+        assert!(breaker.allow().is_none());
+
+        // The trial never reports back; after another cooldown a new one passes
+        expire(&breaker);
+        assert!(breaker.allow().is_some());
+        assert_eq!(breaker.state_gauge.get(), 2);
+    }
+
+    #[test]
+    fn test_dropped_trial_guard_records_a_failure() {
+        let breaker = breaker(1, 3600);
+        breaker.record(true);
+        expire(&breaker);
+        assert_eq!(breaker.allow(), Some(Permit::Trial));
+        drop(breaker.guard(Permit::Trial));
+        assert_eq!(breaker.state_gauge.get(), 1);
+
+        expire(&breaker);
+        assert_eq!(breaker.allow(), Some(Permit::Trial));
+        breaker.guard(Permit::Trial).record(false);
+        assert_eq!(breaker.state_gauge.get(), 0);
+    }
+
+    #[test]
+    fn test_dropped_closed_guards_record_nothing() {
+        let breaker = breaker(2, 3600);
+        for _ in 0..10 {
+            assert_eq!(breaker.allow(), Some(Permit::Normal));
+            drop(breaker.guard(Permit::Normal));
+        }
+        assert_eq!(breaker.state_gauge.get(), 0);
+        assert_eq!(breaker.allow(), Some(Permit::Normal));
+    }
+
+    #[test]
+    fn test_disabled_breaker_never_opens() {
+        let breaker = breaker(0, 3600);
+        for _ in 0..10 {
+            breaker.record(true);
+        }
+        assert!(breaker.allow().is_some());
+    }
+}
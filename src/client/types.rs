@@ -1,6 +1,7 @@
 //! PBS API response types used by the client and metrics collector.
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Node status information from PBS.
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -21,6 +22,13 @@ pub struct NodeStatus {
     pub uptime: u64,
 }
 
+/// Node entry from `/api2/json/nodes`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NodeInfo {
+    /// Node name
+    pub node: String,
+}
+
 /// Memory information.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Memory {
@@ -54,6 +62,47 @@ pub struct DatastoreUsage {
     pub used: u64,
     /// Available bytes
     pub avail: u64,
+    /// PBS estimate of when the datastore will be full (Unix epoch), based on
+    /// a linear regression over the last month of usage
+    #[serde(rename = "estimated-full-date", default)]
+    pub estimated_full_date: Option<i64>,
+    /// Usage history as fractions of the total size (oldest first, gaps as `None`)
+    #[serde(default)]
+    pub history: Option<Vec<Option<f64>>>,
+    /// Timestamp of the first history entry (Unix epoch)
+    #[serde(rename = "history-start", default)]
+    pub history_start: Option<i64>,
+    /// Seconds between history entries
+    #[serde(rename = "history-delta", default)]
+    pub history_delta: Option<i64>,
+}
+
+/// Detailed status of one datastore from `/admin/datastore/{store}/status`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DatastoreStatus {
+    /// Total size in bytes
+    pub total: u64,
+    /// Used bytes
+    pub used: u64,
+    /// Available bytes
+    pub avail: u64,
+    /// Group and snapshot counts keyed by backup type (vm, ct, host, other)
+    #[serde(default)]
+    pub counts: Option<BTreeMap<String, BackupTypeCounts>>,
+    /// Garbage collection status embedded in the datastore status
+    #[serde(rename = "gc-status", default)]
+    pub gc_status: Option<GcStatus>,
+}
+
+/// Group and snapshot counts for one backup type.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BackupTypeCounts {
+    /// Number of backup groups
+    #[serde(default)]
+    pub groups: u64,
+    /// Number of snapshots
+    #[serde(default)]
+    pub snapshots: u64,
 }
 
 /// Backup group information.
@@ -74,6 +123,19 @@ pub struct BackupGroup {
     /// Optional comment
     #[serde(default)]
     pub comment: Option<String>,
+    /// User or API token owning the group
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Namespace the group lives in (root if absent); set by the client
+    #[serde(default)]
+    pub ns: Option<String>,
+}
+
+/// Namespace entry from `/admin/datastore/{store}/namespace`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NamespaceInfo {
+    /// Namespace path, e.g. "prod/db" (empty for the root namespace)
+    pub ns: String,
 }
 
 /// Snapshot information from PBS.
@@ -100,6 +162,60 @@ pub struct Snapshot {
     /// Verification status
     #[serde(default)]
     pub verification: Option<VerificationStatus>,
+    /// Archives and blobs in the snapshot
+    #[serde(default)]
+    pub files: Vec<SnapshotFile>,
+    /// User or API token owning the snapshot's group
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Namespace the snapshot lives in (root if absent); set by the client
+    #[serde(default)]
+    pub ns: Option<String>,
+}
+
+/// Manifest of a snapshot; PBS never encrypts it, so it does not count
+/// towards the snapshot's crypt mode.
+const MANIFEST_FILENAME: &str = "index.json.blob";
+
+impl Snapshot {
+    /// Combined crypt mode of the snapshot's files, ignoring the manifest and
+    /// files PBS lists without a crypt mode (such as `client.log.blob`).
+    ///
+    /// Returns `encrypt`, `sign-only` or `none` when all files share that mode,
+    /// `mixed` otherwise, and `None` if no file has a crypt mode.
+    pub fn crypt_mode(&self) -> Option<&str> {
+        let mut modes = self
+            .files
+            .iter()
+            .filter(|file| file.filename != MANIFEST_FILENAME)
+            .filter_map(SnapshotFile::crypt_mode);
+        let first = modes.next()?;
+        Some(if modes.all(|mode| mode == first) {
+            first
+        } else {
+            "mixed"
+        })
+    }
+}
+
+/// Archive or blob of a snapshot.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SnapshotFile {
+    /// Archive name, e.g. "drive-scsi0.img.fidx" or "root.pxar.didx"
+    pub filename: String,
+    /// Archive size in bytes
+    #[serde(default)]
+    pub size: Option<u64>,
+    /// Encryption of the archive ("none", "sign-only" or "encrypt")
+    #[serde(rename = "crypt-mode", default)]
+    pub crypt_mode: Option<String>,
+}
+
+impl SnapshotFile {
+    /// Crypt mode of the archive, if PBS reported one.
+    pub fn crypt_mode(&self) -> Option<&str> {
+        self.crypt_mode.as_deref()
+    }
 }
 
 /// Verification status information.
@@ -151,9 +267,21 @@ pub struct GcStatus {
     /// Total bytes on disk
     #[serde(rename = "disk-bytes")]
     pub disk_bytes: Option<u64>,
+    /// Chunks on disk
+    #[serde(rename = "disk-chunks")]
+    pub disk_chunks: Option<u64>,
+    /// Logical bytes referenced by all index files
+    #[serde(rename = "index-data-bytes")]
+    pub index_data_bytes: Option<u64>,
+    /// Index files processed by the last GC
+    #[serde(rename = "index-file-count")]
+    pub index_file_count: Option<u64>,
     /// Bytes reclaimed in last GC
     #[serde(rename = "removed-bytes")]
     pub removed_bytes: Option<u64>,
+    /// Chunks removed in last GC
+    #[serde(rename = "removed-chunks")]
+    pub removed_chunks: Option<u64>,
     /// Bytes that can be reclaimed
     #[serde(rename = "pending-bytes")]
     pub pending_bytes: Option<u64>,
@@ -167,8 +295,20 @@ pub struct GcStatus {
     pub duration: Option<f64>,
 }
 
+impl GcStatus {
+    /// Logical index bytes per physical byte on disk.
+    ///
+    /// Returns `None` unless both sizes are known and the disk usage is non-zero.
+    pub fn deduplication_ratio(&self) -> Option<f64> {
+        match (self.index_data_bytes, self.disk_bytes) {
+            (Some(index), Some(disk)) if disk > 0 => Some(index as f64 / disk as f64),
+            _ => None,
+        }
+    }
+}
+
 /// Tape drive information.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct TapeDrive {
     /// Drive name
     pub name: String,
@@ -181,4 +321,308 @@ pub struct TapeDrive {
     /// Serial number
     #[serde(default)]
     pub serial: Option<String>,
+    /// Changer the drive belongs to (standalone drive if absent)
+    #[serde(default)]
+    pub changer: Option<String>,
+    /// Drive number within the changer
+    #[serde(rename = "changer-drivenum", default)]
+    pub changer_drivenum: Option<u64>,
+    /// Task holding the drive lock (idle if absent)
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Current drive activity (e.g. "no-activity", "reading", "writing")
+    #[serde(default)]
+    pub activity: Option<String>,
+}
+
+/// Live drive and loaded medium status from `/tape/drive/{name}/status`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TapeDriveStatus {
+    /// Whether hardware compression is enabled
+    #[serde(default)]
+    pub compression: Option<bool>,
+    /// Whether the loaded medium is write protected
+    #[serde(rename = "write-protect", default)]
+    pub write_protect: Option<bool>,
+    /// Active TapeAlert flags, e.g. `TapeAlertFlags(MEDIA_LIFE | CLEAN_NOW)`
+    #[serde(rename = "alert-flags", default)]
+    pub alert_flags: Option<String>,
+    /// Current drive activity
+    #[serde(rename = "drive-activity", default)]
+    pub drive_activity: Option<String>,
+}
+
+impl TapeDriveStatus {
+    /// Names of the active TapeAlert flags.
+    pub fn alerts(&self) -> Vec<&str> {
+        let Some(flags) = self.alert_flags.as_deref() else {
+            return Vec::new();
+        };
+        let flags = match (flags.find('('), flags.rfind(')')) {
+            (Some(start), Some(end)) if start < end => &flags[start + 1..end],
+            _ => flags,
+        };
+        flags
+            .split('|')
+            .map(str::trim)
+            .filter(|flag| !flag.is_empty() && !flag.starts_with("0x"))
+            .collect()
+    }
+}
+
+/// Tape changer (media library) from `/tape/changer`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TapeChanger {
+    /// Changer name
+    pub name: String,
+    /// Vendor
+    #[serde(default)]
+    pub vendor: Option<String>,
+    /// Model
+    #[serde(default)]
+    pub model: Option<String>,
+    /// Serial number
+    #[serde(default)]
+    pub serial: Option<String>,
+}
+
+/// Drive, storage slot or import/export slot from `/tape/changer/{name}/status`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChangerEntry {
+    /// Element type ("drive", "slot" or "import-export")
+    #[serde(rename = "entry-kind")]
+    pub entry_kind: String,
+    /// Element number (drive number for drives)
+    #[serde(rename = "entry-id")]
+    pub entry_id: u64,
+    /// Label of the medium in the element (empty if unlabeled, absent if empty)
+    #[serde(rename = "label-text", default)]
+    pub label_text: Option<String>,
+    /// Slot the loaded medium came from (drives only)
+    #[serde(rename = "loaded-slot", default)]
+    pub loaded_slot: Option<u64>,
+}
+
+impl ChangerEntry {
+    /// Whether the element holds a medium.
+    pub fn is_occupied(&self) -> bool {
+        self.label_text.is_some() || self.loaded_slot.is_some()
+    }
+}
+
+/// Schedule state shared by all PBS job types (sync, verify, prune, tape backup).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct JobScheduleStatus {
+    /// Next scheduled run timestamp
+    #[serde(rename = "next-run", default)]
+    pub next_run: Option<i64>,
+    /// State of the last run ("OK" or an error message)
+    #[serde(rename = "last-run-state", default)]
+    pub last_run_state: Option<String>,
+    /// Task ID of the last run
+    #[serde(rename = "last-run-upid", default)]
+    pub last_run_upid: Option<String>,
+    /// Completion timestamp of the last run
+    #[serde(rename = "last-run-endtime", default)]
+    pub last_run_endtime: Option<i64>,
+}
+
+impl JobScheduleStatus {
+    /// Duration of the last finished run in seconds.
+    ///
+    /// PBS only reports when the last run ended; its start time is encoded in
+    /// the task's UPID.
+    pub fn last_run_duration(&self) -> Option<i64> {
+        let start = upid_starttime(self.last_run_upid.as_deref()?)?;
+        let end = self.last_run_endtime?;
+        (end >= start).then_some(end - start)
+    }
+}
+
+/// Start timestamp encoded in a UPID
+/// (`UPID:node:pid:pstart:task_id:starttime:worker_type:worker_id:auth_id:`).
+fn upid_starttime(upid: &str) -> Option<i64> {
+    let mut fields = upid.split(':');
+    if fields.next()? != "UPID" {
+        return None;
+    }
+    i64::from_str_radix(fields.nth(4)?, 16).ok()
+}
+
+/// Sync job configuration and schedule state from `/admin/sync`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SyncJob {
+    /// Job ID
+    pub id: String,
+    /// Local datastore
+    pub store: String,
+    /// Local namespace (root if absent)
+    #[serde(default)]
+    pub ns: Option<String>,
+    /// Remote PBS (absent for local sync jobs)
+    #[serde(default)]
+    pub remote: Option<String>,
+    /// Datastore on the remote
+    #[serde(rename = "remote-store")]
+    pub remote_store: String,
+    /// Namespace on the remote (root if absent)
+    #[serde(rename = "remote-ns", default)]
+    pub remote_ns: Option<String>,
+    /// Schedule (calendar event)
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// Last/next run state
+    #[serde(flatten)]
+    pub status: JobScheduleStatus,
+}
+
+/// Verification job configuration and schedule state from `/admin/verify`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VerificationJob {
+    /// Job ID
+    pub id: String,
+    /// Datastore to verify
+    pub store: String,
+    /// Namespace to verify (root if absent)
+    #[serde(default)]
+    pub ns: Option<String>,
+    /// Schedule (calendar event)
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// Days after which a verified snapshot is verified again
+    #[serde(rename = "outdated-after", default)]
+    pub outdated_after: Option<i64>,
+    /// Skip snapshots that are already verified (PBS defaults to true when unset)
+    #[serde(rename = "ignore-verified", default)]
+    pub ignore_verified: Option<bool>,
+    /// Last/next run state
+    #[serde(flatten)]
+    pub status: JobScheduleStatus,
+}
+
+/// Prune job configuration and schedule state from `/admin/prune`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PruneJob {
+    /// Job ID
+    pub id: String,
+    /// Datastore to prune
+    pub store: String,
+    /// Namespace to prune (root if absent)
+    #[serde(default)]
+    pub ns: Option<String>,
+    /// Schedule (calendar event)
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// Retention settings
+    #[serde(flatten)]
+    pub keep: KeepOptions,
+    /// Last/next run state
+    #[serde(flatten)]
+    pub status: JobScheduleStatus,
+}
+
+/// Prune retention settings (`keep-*`); unset options keep nothing extra.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct KeepOptions {
+    /// Number of most recent backups to keep
+    #[serde(rename = "keep-last", default)]
+    pub keep_last: Option<u64>,
+    /// Number of hourly backups to keep
+    #[serde(rename = "keep-hourly", default)]
+    pub keep_hourly: Option<u64>,
+    /// Number of daily backups to keep
+    #[serde(rename = "keep-daily", default)]
+    pub keep_daily: Option<u64>,
+    /// Number of weekly backups to keep
+    #[serde(rename = "keep-weekly", default)]
+    pub keep_weekly: Option<u64>,
+    /// Number of monthly backups to keep
+    #[serde(rename = "keep-monthly", default)]
+    pub keep_monthly: Option<u64>,
+    /// Number of yearly backups to keep
+    #[serde(rename = "keep-yearly", default)]
+    pub keep_yearly: Option<u64>,
+}
+
+impl KeepOptions {
+    /// Configured options as (`keep` label value, count) pairs.
+    pub fn configured(&self) -> impl Iterator<Item = (&'static str, u64)> {
+        [
+            ("last", self.keep_last),
+            ("hourly", self.keep_hourly),
+            ("daily", self.keep_daily),
+            ("weekly", self.keep_weekly),
+            ("monthly", self.keep_monthly),
+            ("yearly", self.keep_yearly),
+        ]
+        .into_iter()
+        .filter_map(|(name, count)| Some((name, count?)))
+    }
+}
+
+/// Tape backup job configuration and schedule state from `/tape/backup`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TapeBackupJob {
+    /// Job ID
+    pub id: String,
+    /// Datastore to back up
+    pub store: String,
+    /// Target media pool
+    pub pool: String,
+    /// Tape drive used by the job
+    pub drive: String,
+    /// Namespace to back up (root if absent)
+    #[serde(default)]
+    pub ns: Option<String>,
+    /// Schedule (calendar event)
+    #[serde(default)]
+    pub schedule: Option<String>,
+    /// Label of the medium the next run will write to
+    #[serde(rename = "next-media-label", default)]
+    pub next_media_label: Option<String>,
+    /// Last/next run state
+    #[serde(flatten)]
+    pub status: JobScheduleStatus,
+}
+
+/// Media pool configuration from `/config/media-pool`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MediaPool {
+    /// Pool name
+    pub name: String,
+    /// Media set allocation policy (e.g. "continue", "always" or a calendar event)
+    #[serde(default)]
+    pub allocation: Option<String>,
+    /// Media set retention policy (e.g. "keep", "overwrite" or a time span)
+    #[serde(default)]
+    pub retention: Option<String>,
+    /// Fingerprint of the encryption key (unencrypted if absent)
+    #[serde(default)]
+    pub encrypt: Option<String>,
+}
+
+/// Tape medium from the media inventory (`/tape/media/list`).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TapeMedia {
+    /// Medium UUID
+    pub uuid: String,
+    /// Label text (barcode)
+    #[serde(rename = "label-text")]
+    pub label_text: String,
+    /// Location ("offline", "online-<changer>" or "vault-<vault>")
+    pub location: String,
+    /// Status ("full", "writable", "unknown", "damaged" or "retired")
+    pub status: String,
+    /// Whether the medium's media set has passed its retention period
+    #[serde(default)]
+    pub expired: bool,
+    /// Media pool (absent for unassigned media)
+    #[serde(default)]
+    pub pool: Option<String>,
+    /// Media set name
+    #[serde(rename = "media-set-name", default)]
+    pub media_set_name: Option<String>,
+    /// Bytes written to the medium
+    #[serde(rename = "bytes-used", default)]
+    pub bytes_used: Option<u64>,
 }
@@ -7,6 +7,7 @@
 
 use crate::error::{PbsError, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
 
 /// PBS server connection settings.
@@ -34,6 +35,103 @@ pub struct PbsConfig {
     /// Number of snapshots to expose per backup group (0 = all, 1 = latest only, 2 = 2 latest, etc.)
     #[serde(default = "default_snapshot_history_limit")]
     pub snapshot_history_limit: usize,
+
+    /// Namespace levels below the root to collect snapshots and groups from
+    /// (0 = root namespace only, up to PBS's limit of 7)
+    #[serde(default = "default_namespace_max_depth")]
+    pub namespace_max_depth: usize,
+
+    /// Maximum number of concurrent API requests to PBS during a collection
+    #[serde(default = "default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+
+    /// Node names to query for host and task metrics
+    #[serde(default = "default_nodes")]
+    pub nodes: Vec<String>,
+
+    /// Discover node names via `/api2/json/nodes` instead of using `nodes`
+    #[serde(default)]
+    pub discover_nodes: bool,
+
+    /// Retry policy for transient request failures
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    /// Circuit breaker that stops querying an unreachable PBS
+    #[serde(default)]
+    pub circuit_breaker: CircuitBreakerConfig,
+}
+
+/// Retry policy for idempotent PBS API requests.
+///
+/// Only transient failures (connection errors, timeouts, HTTP 429/502/503/504)
+/// are retried, with jittered exponential backoff between attempts.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RetryConfig {
+    /// Retries after the first attempt (0 = no retries)
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    /// Backoff before the first retry in milliseconds, doubled for every further retry
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Upper bound for the backoff in milliseconds
+    #[serde(default = "default_max_backoff_ms")]
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: default_max_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+        }
+    }
+}
+
+/// Circuit breaker settings.
+///
+/// After `failure_threshold` consecutive requests fail with a transient error,
+/// requests are rejected without contacting PBS for `open_seconds`; then a
+/// single trial request decides whether to close the breaker again.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failed requests that open the breaker (0 = disabled)
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+    /// Seconds to stay open before allowing a trial request
+    #[serde(default = "default_open_seconds")]
+    pub open_seconds: u64,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: default_failure_threshold(),
+            open_seconds: default_open_seconds(),
+        }
+    }
+}
+
+impl Default for PbsConfig {
+    /// Default settings without an endpoint or credentials, standing in for a
+    /// missing `[pbs]` section.
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            token_id: String::new(),
+            token_secret: String::new(),
+            verify_tls: default_verify_tls(),
+            timeout_seconds: default_timeout(),
+            snapshot_history_limit: default_snapshot_history_limit(),
+            namespace_max_depth: default_namespace_max_depth(),
+            max_concurrent_requests: default_max_concurrent_requests(),
+            nodes: default_nodes(),
+            discover_nodes: false,
+            retry: RetryConfig::default(),
+            circuit_breaker: CircuitBreakerConfig::default(),
+        }
+    }
 }
 
 impl std::fmt::Debug for PbsConfig {
@@ -45,6 +143,12 @@ impl std::fmt::Debug for PbsConfig {
             .field("verify_tls", &self.verify_tls)
             .field("timeout_seconds", &self.timeout_seconds)
             .field("snapshot_history_limit", &self.snapshot_history_limit)
+            .field("namespace_max_depth", &self.namespace_max_depth)
+            .field("max_concurrent_requests", &self.max_concurrent_requests)
+            .field("nodes", &self.nodes)
+            .field("discover_nodes", &self.discover_nodes)
+            .field("retry", &self.retry)
+            .field("circuit_breaker", &self.circuit_breaker)
             .finish()
     }
 }
@@ -58,16 +162,249 @@ pub struct ExporterConfig {
     /// Log level (trace, debug, info, warn, error)
     #[serde(default = "default_log_level")]
     pub log_level: String,
+    /// Background collection interval in seconds (0 = collect on every scrape)
+    #[serde(default = "default_collection_interval")]
+    pub collection_interval_seconds: u64,
+    /// How `[targets]` are exposed (`probe` or `static`)
+    #[serde(default)]
+    pub target_mode: TargetMode,
+    /// Hours of datastore usage used for the exporter's fill-rate forecast (0 = disabled)
+    #[serde(default = "default_forecast_window_hours")]
+    pub forecast_window_hours: u64,
+    /// Add the backup owner as an `owner` label to backup group metrics
+    #[serde(default)]
+    pub owner_label: bool,
+    /// Days covered by the usage report's growth column (0 = disabled)
+    #[serde(default = "default_usage_growth_days")]
+    pub usage_growth_days: u64,
+}
+
+/// `pbs_server` label value of the `[pbs]` server in static target mode.
+pub const DEFAULT_TARGET_NAME: &str = "default";
+
+/// Deepest namespace level PBS supports below the root namespace.
+pub const MAX_NAMESPACE_DEPTH: usize = 7;
+
+/// How the named `[targets]` are exposed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TargetMode {
+    /// Each target is scraped separately via `/probe?target=<name>`
+    #[default]
+    Probe,
+    /// `/metrics` exposes all servers at once, labelled with `pbs_server`
+    Static,
+}
+
+/// Enables or disables individual collectors (all enabled by default).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CollectorsConfig {
+    /// Host metrics from the node status endpoint
+    #[serde(default = "default_collector_enabled")]
+    pub node: bool,
+    /// Datastore capacity metrics
+    #[serde(default = "default_collector_enabled")]
+    pub datastore: bool,
+    /// Per-datastore group and snapshot counts (one API call per datastore)
+    #[serde(default = "default_collector_enabled")]
+    pub datastore_status: bool,
+    /// Individual snapshot metrics (one API call per datastore)
+    #[serde(default = "default_collector_enabled")]
+    pub snapshots: bool,
+    /// Backup group metrics (one API call per datastore)
+    #[serde(default = "default_collector_enabled")]
+    pub groups: bool,
+    /// Task metrics
+    #[serde(default = "default_collector_enabled")]
+    pub tasks: bool,
+    /// Garbage collection metrics (one API call per datastore)
+    #[serde(default = "default_collector_enabled")]
+    pub gc: bool,
+    /// Sync job metrics
+    #[serde(default = "default_collector_enabled")]
+    pub sync_jobs: bool,
+    /// Verification job metrics
+    #[serde(default = "default_collector_enabled")]
+    pub verify_jobs: bool,
+    /// Prune job metrics
+    #[serde(default = "default_collector_enabled")]
+    pub prune_jobs: bool,
+    /// Tape drive metrics
+    #[serde(default = "default_collector_enabled")]
+    pub tape: bool,
+    /// PBS version information
+    #[serde(default = "default_collector_enabled")]
+    pub version: bool,
+}
+
+impl CollectorsConfig {
+    /// Names of the collectors that are switched on.
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [
+            ("node", self.node),
+            ("datastore", self.datastore),
+            ("datastore_status", self.datastore_status),
+            ("snapshots", self.snapshots),
+            ("groups", self.groups),
+            ("tasks", self.tasks),
+            ("gc", self.gc),
+            ("sync_jobs", self.sync_jobs),
+            ("verify_jobs", self.verify_jobs),
+            ("prune_jobs", self.prune_jobs),
+            ("tape", self.tape),
+            ("version", self.version),
+        ]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect()
+    }
 }
 
+impl Default for CollectorsConfig {
+    fn default() -> Self {
+        Self {
+            node: true,
+            datastore: true,
+            datastore_status: true,
+            snapshots: true,
+            groups: true,
+            tasks: true,
+            gc: true,
+            sync_jobs: true,
+            verify_jobs: true,
+            prune_jobs: true,
+            tape: true,
+            version: true,
+        }
+    }
+}
+
+/// Include/exclude patterns for one backup group property.
+///
+/// Patterns are globs (`*`, `?`, `[abc]`) matched against the whole value, or
+/// regular expressions when prefixed with `re:`. A value passes when it matches
+/// any `include` pattern (or `include` is empty) and no `exclude` pattern.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FilterRule {
+    /// Patterns of values to keep (empty = keep everything)
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Patterns of values to drop, applied after `include`
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
+/// Selects the backup groups exposed in snapshot and group metrics.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FiltersConfig {
+    /// Datastore names
+    #[serde(default)]
+    pub datastores: FilterRule,
+    /// Namespaces (empty string = root namespace)
+    #[serde(default)]
+    pub namespaces: FilterRule,
+    /// Backup types (`vm`, `ct`, `host`)
+    #[serde(default)]
+    pub backup_types: FilterRule,
+    /// Backup IDs
+    #[serde(default)]
+    pub backup_ids: FilterRule,
+}
+
+/// Backup freshness SLO for the backup groups matching its patterns.
+///
+/// Patterns use the same syntax as [`FilterRule`]. The first rule matching a
+/// group sets its maximum backup age; groups without a matching rule have no SLO.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct FreshnessRule {
+    /// Pattern matched against `<backup_type>/<backup_id>`, e.g. `vm/*`
+    pub group: String,
+    /// Pattern restricting the rule to matching datastores (any if unset)
+    #[serde(default)]
+    pub datastore: Option<String>,
+    /// Pattern restricting the rule to matching namespaces (any if unset)
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Maximum age of the latest backup in hours before the group is stale
+    pub max_age_hours: f64,
+}
+
+/// Snapshots expected to exist for the backup groups matching its patterns.
+///
+/// Each `keep_*` count is a number of consecutive calendar periods (UTC), ending
+/// with the current one, that must each contain at least one snapshot; the
+/// current period is only expected once it has a snapshot. Group, datastore and
+/// namespace patterns work as in [`FreshnessRule`]; the first matching rule wins.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RetentionRule {
+    /// Pattern matched against `<backup_type>/<backup_id>`, e.g. `vm/*`
+    pub group: String,
+    /// Pattern restricting the rule to matching datastores (any if unset)
+    #[serde(default)]
+    pub datastore: Option<String>,
+    /// Pattern restricting the rule to matching namespaces (any if unset)
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Expected hourly snapshots
+    #[serde(default)]
+    pub keep_hourly: u32,
+    /// Expected daily snapshots
+    #[serde(default)]
+    pub keep_daily: u32,
+    /// Expected weekly snapshots (ISO weeks, starting on Monday)
+    #[serde(default)]
+    pub keep_weekly: u32,
+    /// Expected monthly snapshots
+    #[serde(default)]
+    pub keep_monthly: u32,
+    /// Expected yearly snapshots
+    #[serde(default)]
+    pub keep_yearly: u32,
+}
+
+/// Filter lists that may be given as comma-separated environment variables.
+const FILTER_LIST_KEYS: [&str; 8] = [
+    "filters.datastores.include",
+    "filters.datastores.exclude",
+    "filters.namespaces.include",
+    "filters.namespaces.exclude",
+    "filters.backup_types.include",
+    "filters.backup_types.exclude",
+    "filters.backup_ids.include",
+    "filters.backup_ids.exclude",
+];
+
 /// Main configuration structure for the PBS exporter.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Settings {
-    /// PBS server configuration
+    /// Default PBS server, exposed on `/metrics`; may be left out when `targets`
+    /// are configured, see [`Settings::default_pbs`]
+    #[serde(default)]
     pub pbs: PbsConfig,
 
+    /// Named PBS servers, scraped through `/probe?target=<name>` or, in static
+    /// target mode, exposed together on `/metrics`
+    #[serde(default)]
+    pub targets: BTreeMap<String, PbsConfig>,
+
     /// Exporter server configuration
     pub exporter: ExporterConfig,
+
+    /// Collector selection
+    #[serde(default)]
+    pub collectors: CollectorsConfig,
+
+    /// Backup group include/exclude filters
+    #[serde(default)]
+    pub filters: FiltersConfig,
+
+    /// Backup freshness SLOs, first match wins
+    #[serde(default)]
+    pub freshness: Vec<FreshnessRule>,
+
+    /// Expected retention per backup group, first match wins
+    #[serde(default)]
+    pub retention: Vec<RetentionRule>,
 }
 
 fn default_verify_tls() -> bool {
@@ -82,6 +419,50 @@ fn default_snapshot_history_limit() -> usize {
     0 // 0 means all snapshots (full timeline)
 }
 
+fn default_namespace_max_depth() -> usize {
+    MAX_NAMESPACE_DEPTH
+}
+
+fn default_max_concurrent_requests() -> usize {
+    4
+}
+
+fn default_forecast_window_hours() -> u64 {
+    168
+}
+
+fn default_usage_growth_days() -> u64 {
+    30
+}
+
+fn default_nodes() -> Vec<String> {
+    vec!["localhost".to_string()]
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    200
+}
+
+fn default_max_backoff_ms() -> u64 {
+    2000
+}
+
+fn default_failure_threshold() -> u32 {
+    5
+}
+
+fn default_open_seconds() -> u64 {
+    30
+}
+
+fn default_collector_enabled() -> bool {
+    true
+}
+
 fn default_listen_address() -> String {
     "0.0.0.0:9101".to_string()
 }
@@ -90,6 +471,10 @@ fn default_log_level() -> String {
     "info".to_string()
 }
 
+fn default_collection_interval() -> u64 {
+    0 // 0 means collect on every scrape
+}
+
 impl Settings {
     /// Load configuration from a file and environment variables.
     ///
@@ -114,6 +499,7 @@ impl Settings {
     /// Returns an error if:
     /// - The configuration file exists but cannot be parsed
     /// - Environment variables cannot be parsed
+    /// - Neither a default PBS server nor any named target is configured
     /// - Required fields (endpoint, token_id, token_secret) are missing or empty
     /// - The resulting configuration fails validation
     ///
@@ -144,11 +530,15 @@ impl Settings {
         }
 
         // Add environment variables with PBS_EXPORTER_ prefix
-        builder = builder.add_source(
-            config::Environment::with_prefix("PBS_EXPORTER")
-                .separator("__")
-                .try_parsing(true),
-        );
+        let mut environment = config::Environment::with_prefix("PBS_EXPORTER")
+            .separator("__")
+            .try_parsing(true)
+            .list_separator(",")
+            .with_list_parse_key("pbs.nodes");
+        for key in FILTER_LIST_KEYS {
+            environment = environment.with_list_parse_key(key);
+        }
+        builder = builder.add_source(environment);
 
         let config = builder.build()?;
         let settings: Settings = config.try_deserialize()?;
@@ -157,18 +547,104 @@ impl Settings {
         Ok(settings)
     }
 
+    /// The default PBS server exposed on `/metrics`.
+    ///
+    /// `None` if named `targets` are configured and `[pbs]` sets no endpoint;
+    /// without targets, `[pbs]` is always required.
+    pub fn default_pbs(&self) -> Option<&PbsConfig> {
+        (self.targets.is_empty() || !self.pbs.endpoint.is_empty()).then_some(&self.pbs)
+    }
+
     /// Validate configuration settings.
     fn validate(&self) -> Result<()> {
-        if self.pbs.endpoint.is_empty() {
+        if self.pbs.endpoint.is_empty() && self.targets.is_empty() {
             return Err(PbsError::Config(config::ConfigError::Message(
-                "PBS endpoint cannot be empty".to_string(),
+                "No PBS server configured: set [pbs] or add at least one [targets.<name>]"
+                    .to_string(),
             )));
         }
 
-        if self.pbs.token_id.is_empty() || self.pbs.token_secret.is_empty() {
-            return Err(PbsError::Config(config::ConfigError::Message(
-                "PBS API token credentials are required".to_string(),
-            )));
+        if let Some(pbs) = self.default_pbs() {
+            pbs.validate("")?;
+        }
+
+        for (name, target) in &self.targets {
+            target.validate(&format!(" (target {})", name))?;
+        }
+
+        if self.exporter.target_mode == TargetMode::Static
+            && self.default_pbs().is_some()
+            && self.targets.contains_key(DEFAULT_TARGET_NAME)
+        {
+            return Err(PbsError::Config(config::ConfigError::Message(format!(
+                "Target name '{}' is reserved for [pbs] in static target mode",
+                DEFAULT_TARGET_NAME
+            ))));
+        }
+
+        Ok(())
+    }
+}
+
+impl PbsConfig {
+    /// Connection settings for `endpoint` with the given API token and
+    /// defaults for everything else.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use pbs_exporter::config::PbsConfig;
+    ///
+    /// let config = PbsConfig {
+    ///     verify_tls: true,
+    ///     ..PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret")
+    /// };
+    /// assert_eq!(config.timeout_seconds, 5);
+    /// ```
+    pub fn new(endpoint: &str, token_id: &str, token_secret: &str) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            token_id: token_id.to_string(),
+            token_secret: token_secret.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Validate connection settings; `context` is appended to error messages.
+    fn validate(&self, context: &str) -> Result<()> {
+        if self.endpoint.is_empty() {
+            return Err(PbsError::Config(config::ConfigError::Message(format!(
+                "PBS endpoint cannot be empty{}",
+                context
+            ))));
+        }
+
+        if self.token_id.is_empty() || self.token_secret.is_empty() {
+            return Err(PbsError::Config(config::ConfigError::Message(format!(
+                "PBS API token credentials are required{}",
+                context
+            ))));
+        }
+
+        if self.max_concurrent_requests == 0 {
+            return Err(PbsError::Config(config::ConfigError::Message(format!(
+                "PBS max_concurrent_requests must be at least 1{}",
+                context
+            ))));
+        }
+
+        if self.namespace_max_depth > MAX_NAMESPACE_DEPTH {
+            return Err(PbsError::Config(config::ConfigError::Message(format!(
+                "PBS namespace_max_depth cannot exceed {}{}",
+                MAX_NAMESPACE_DEPTH, context
+            ))));
+        }
+
+        if self.nodes.is_empty() && !self.discover_nodes {
+            return Err(PbsError::Config(config::ConfigError::Message(format!(
+                "PBS nodes cannot be empty unless discover_nodes is set{}",
+                context
+            ))));
         }
 
         Ok(())
@@ -178,18 +654,21 @@ impl Settings {
 impl Default for Settings {
     fn default() -> Self {
         Self {
-            pbs: PbsConfig {
-                endpoint: "https://localhost:8007".to_string(),
-                token_id: String::new(),
-                token_secret: String::new(),
-                verify_tls: default_verify_tls(),
-                timeout_seconds: default_timeout(),
-                snapshot_history_limit: default_snapshot_history_limit(),
-            },
+            pbs: PbsConfig::new("https://localhost:8007", "", ""),
+            targets: BTreeMap::new(),
             exporter: ExporterConfig {
                 listen_address: default_listen_address(),
                 log_level: default_log_level(),
+                collection_interval_seconds: default_collection_interval(),
+                target_mode: TargetMode::default(),
+                forecast_window_hours: default_forecast_window_hours(),
+                owner_label: false,
+                usage_growth_days: default_usage_growth_days(),
             },
+            collectors: CollectorsConfig::default(),
+            filters: FiltersConfig::default(),
+            freshness: Vec::new(),
+            retention: Vec::new(),
         }
     }
 }
@@ -201,10 +680,14 @@ mod tests {
     #[test]
     fn test_default_settings() {
         let settings = Settings::default();
-        assert_eq!(settings.pbs.endpoint, "https://localhost:8007");
+        let pbs = &settings.pbs;
+        assert_eq!(pbs.endpoint, "https://localhost:8007");
         assert_eq!(settings.exporter.listen_address, "0.0.0.0:9101");
-        assert!(!settings.pbs.verify_tls);
-        assert_eq!(settings.pbs.snapshot_history_limit, 0);
+        assert!(!pbs.verify_tls);
+        assert_eq!(pbs.snapshot_history_limit, 0);
+        assert_eq!(settings.exporter.collection_interval_seconds, 0);
+        assert_eq!(pbs.max_concurrent_requests, 4);
+        assert!(settings.targets.is_empty());
     }
 
     #[test]
@@ -212,4 +695,82 @@ mod tests {
         let settings = Settings::default();
         assert!(settings.validate().is_err());
     }
+
+    #[test]
+    fn test_collectors_enabled_by_default() {
+        let settings = Settings::default();
+        assert_eq!(settings.collectors.enabled().len(), 12);
+    }
+
+    #[test]
+    fn test_disabled_collectors_are_excluded() {
+        let collectors = CollectorsConfig {
+            tape: false,
+            snapshots: false,
+            ..CollectorsConfig::default()
+        };
+        let enabled = collectors.enabled();
+        assert!(!enabled.contains(&"tape"));
+        assert!(!enabled.contains(&"snapshots"));
+        assert!(enabled.contains(&"node"));
+    }
+
+    #[test]
+    fn test_validation_rejects_excessive_namespace_depth() {
+        let mut settings = Settings::default();
+        let pbs = &mut settings.pbs;
+        pbs.token_id = "user@pam!token".to_string();
+        pbs.token_secret = "secret".to_string();
+        pbs.namespace_max_depth = MAX_NAMESPACE_DEPTH;
+        assert!(settings.validate().is_ok());
+
+        settings.pbs.namespace_max_depth = MAX_NAMESPACE_DEPTH + 1;
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_validation_rejects_zero_concurrency() {
+        let mut settings = Settings::default();
+        let pbs = &mut settings.pbs;
+        pbs.token_id = "user@pam!token".to_string();
+        pbs.token_secret = "secret".to_string();
+        assert!(settings.validate().is_ok());
+
+        settings.pbs.max_concurrent_requests = 0;
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_targets_without_default_pbs() {
+        let mut settings = Settings::default();
+        let mut target = std::mem::take(&mut settings.pbs);
+        assert!(settings.validate().is_err());
+
+        target.token_id = "user@pam!token".to_string();
+        target.token_secret = "secret".to_string();
+        settings
+            .targets
+            .insert("offsite".to_string(), target.clone());
+        assert!(settings.validate().is_ok());
+
+        target.token_secret = String::new();
+        settings.targets.insert("broken".to_string(), target);
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn test_static_mode_reserves_default_target_name() {
+        let mut settings = Settings::default();
+        let pbs = &mut settings.pbs;
+        pbs.token_id = "user@pam!token".to_string();
+        pbs.token_secret = "secret".to_string();
+        let target = pbs.clone();
+        settings
+            .targets
+            .insert(DEFAULT_TARGET_NAME.to_string(), target);
+        assert!(settings.validate().is_ok());
+
+        settings.exporter.target_mode = TargetMode::Static;
+        assert!(settings.validate().is_err());
+    }
 }
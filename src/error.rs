@@ -20,14 +20,26 @@ pub enum PbsError {
     #[error("Configuration error: {0}")]
     Config(#[from] config::ConfigError),
 
+    /// Invalid `[filters]`, `[[freshness]]` or `[[retention]]` rule
+    #[error("Invalid rule: {0}")]
+    InvalidRule(String),
+
     /// Authentication error
     #[error("Authentication failed: {0}")]
     Auth(String),
 
+    /// Request rejected because the circuit breaker is open
+    #[error("Circuit breaker open, PBS requests are paused")]
+    CircuitOpen,
+
     /// Metrics error
     #[error("Metrics error: {0}")]
     Metrics(String),
 
+    /// Metrics collection error shared between concurrent scrapes
+    #[error("Metrics collection failed: {0}")]
+    Collection(String),
+
     /// HTTP server error
     #[error("HTTP server error: {0}")]
     Server(String),
@@ -22,7 +22,7 @@
 //!     // Load configuration
 //!     let settings = Settings::load(Some("config/default.toml"))?;
 //!     
-//!     // Create PBS client
+//!     // Create PBS client for the default server
 //!     let client = PbsClient::new(settings.pbs)?;
 //!     
 //!     let metrics = MetricsCollector::new(std::sync::Arc::new(client), 0).unwrap();
@@ -1,8 +1,13 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use clap::Parser;
 use pbs_exporter::{
-    client::PbsClient, config::Settings, metrics::MetricsCollector, server::start_server,
+    client::PbsClient,
+    config::{PbsConfig, Settings, TargetMode, DEFAULT_TARGET_NAME},
+    metrics::{Collector, MetricsCollector},
+    server::start_server_with_targets,
 };
+use std::collections::BTreeMap;
+use std::sync::Arc;
 use tracing::{error, info};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
@@ -27,21 +32,50 @@ async fn main() -> Result<()> {
     init_logging(&settings.exporter.log_level)?;
 
     info!("Starting PBS Exporter");
-    info!("PBS endpoint: {}", settings.pbs.endpoint);
+    if let Some(pbs) = settings.default_pbs() {
+        info!("PBS endpoint: {}", pbs.endpoint);
+    }
+    for (name, target) in &settings.targets {
+        info!("Target {}: {}", name, target.endpoint);
+    }
+    info!("Target mode: {:?}", settings.exporter.target_mode);
     info!("Listen address: {}", settings.exporter.listen_address);
+    match settings.exporter.collection_interval_seconds {
+        0 => info!("Collection mode: on scrape"),
+        secs => info!("Collection mode: background every {} seconds", secs),
+    }
 
-    // Create PBS client
-    let client = PbsClient::new(settings.pbs.clone())?;
-    info!("PBS client initialized");
+    // Create metrics collector for the default PBS server
+    let metrics = match settings.default_pbs() {
+        Some(pbs) => Some(
+            build_collector(DEFAULT_TARGET_NAME, pbs, &settings)?
+                .with_collection_interval(settings.exporter.collection_interval_seconds),
+        ),
+        None => None,
+    };
 
-    // Create metrics collector
-    let client = std::sync::Arc::new(client);
-    let metrics = MetricsCollector::new(client, settings.pbs.snapshot_history_limit)?;
-    info!("Metrics collector initialized");
+    // Create one collector per target; probe-only targets are collected on demand
+    let mut targets = BTreeMap::new();
+    for (name, target) in &settings.targets {
+        let mut collector = build_collector(name, target, &settings)?;
+        if settings.exporter.target_mode == TargetMode::Static {
+            collector =
+                collector.with_collection_interval(settings.exporter.collection_interval_seconds);
+        }
+        targets.insert(name.clone(), collector);
+    }
+    info!("Metrics collectors initialized");
 
     // Start HTTP server
     info!("Starting HTTP server...");
-    if let Err(e) = start_server(&settings.exporter.listen_address, metrics).await {
+    if let Err(e) = start_server_with_targets(
+        &settings.exporter.listen_address,
+        metrics,
+        targets,
+        settings.exporter.target_mode,
+    )
+    .await
+    {
         error!("Server error: {}", e);
         return Err(e.into());
     }
@@ -49,6 +83,33 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
+/// Create a PBS client and metrics collector for one server.
+///
+/// In static target mode the collector labels its series with `pbs_server="<name>"`.
+fn build_collector(name: &str, pbs: &PbsConfig, settings: &Settings) -> Result<MetricsCollector> {
+    let collectors = settings
+        .collectors
+        .enabled()
+        .into_iter()
+        .map(|name| {
+            Collector::from_name(name).ok_or_else(|| anyhow!("Unknown collector '{}'", name))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let client = Arc::new(PbsClient::new(pbs.clone())?);
+    let mut collector = MetricsCollector::new(client, pbs.snapshot_history_limit)?
+        .with_collectors(collectors)
+        .with_forecast_window(settings.exporter.forecast_window_hours)
+        .with_filters(&settings.filters)?
+        .with_freshness_rules(&settings.freshness)?
+        .with_retention_rules(&settings.retention)?
+        .with_owner_label(settings.exporter.owner_label)?
+        .with_usage_growth_days(settings.exporter.usage_growth_days);
+    if settings.exporter.target_mode == TargetMode::Static {
+        collector = collector.with_server_label(name)?;
+    }
+    Ok(collector)
+}
+
 /// Initialize structured logging with tracing.
 fn init_logging(log_level: &str) -> Result<()> {
     let env_filter =
@@ -1,52 +1,277 @@
 //! Metric collection orchestration logic.
 
-use super::{MetricRegistry, MetricsCollector};
-use crate::client::Snapshot;
-use crate::error::Result;
+use super::{
+    BackupFilter, CollectionReceiver, FreshnessRules, InFlightCollection, MetricRegistry,
+    MetricsCollector, RetentionRules, UsageRow,
+};
+use crate::client::{
+    BackupGroup, ChangerEntry, DatastoreStatus, DatastoreUsage, GcStatus, MediaPool, NodeStatus,
+    PbsClient, PruneJob, Snapshot, SyncJob, TapeBackupJob, TapeChanger, TapeDrive, TapeDriveStatus,
+    TapeMedia, Task, VerificationJob, VersionInfo,
+};
+use crate::error::{PbsError, Result};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
-use std::time::Instant;
-use tracing::{error, info};
+use std::future::Future;
+use std::sync::{Arc, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, error, info};
 
-type LatestSnapshotCommentMap = HashMap<(String, String), (i64, Option<String>)>;
+/// Latest snapshot time and comment per (namespace, backup type, backup ID).
+type LatestSnapshotCommentMap = HashMap<(String, String, String), (i64, Option<String>)>;
 type TaskCommentMap = HashMap<String, String>;
 
-/// Collect all metrics from PBS.
+/// Collect all metrics from PBS, joining a collection that is already running.
+///
+/// Concurrent callers share a single in-flight collection: the first caller
+/// spawns it and everyone (including the first caller) waits for its result.
+/// The collection runs in its own task so a cancelled scrape cannot abandon it
+/// half way through.
+pub(super) async fn collect_shared(collector: &MetricsCollector) -> Result<()> {
+    let mut receiver = {
+        let mut in_flight = lock_in_flight(collector.in_flight());
+        match in_flight.as_ref() {
+            Some(receiver) => {
+                debug!("Joining in-flight collection");
+                receiver.clone()
+            }
+            None => {
+                let (sender, receiver) = watch::channel(None);
+                *in_flight = Some(receiver.clone());
+
+                let leader = collector.clone();
+                tokio::spawn(async move {
+                    let _clear = ClearInFlight(leader.in_flight());
+                    let outcome = collect(&leader).await.map_err(|e| e.to_string());
+                    let _ = sender.send(Some(outcome));
+                });
+
+                receiver
+            }
+        }
+    };
+
+    let outcome = receiver
+        .wait_for(Option::is_some)
+        .await
+        .map(|outcome| outcome.clone())
+        .map_err(|_| PbsError::Collection("collection task ended without a result".to_string()))?;
+
+    match outcome {
+        Some(Ok(())) => Ok(()),
+        Some(Err(message)) => Err(PbsError::Collection(message)),
+        None => Err(PbsError::Collection(
+            "collection finished without an outcome".to_string(),
+        )),
+    }
+}
+
+/// Clears the in-flight slot when the collection task finishes (or panics).
+struct ClearInFlight<'a>(&'a InFlightCollection);
+
+impl Drop for ClearInFlight<'_> {
+    fn drop(&mut self) {
+        *lock_in_flight(self.0) = None;
+    }
+}
+
+fn lock_in_flight(in_flight: &InFlightCollection) -> MutexGuard<'_, Option<CollectionReceiver>> {
+    in_flight.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Which state of the collector a collection refreshes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Scope {
+    /// Scheduled or on-scrape collection of all enabled collectors into the
+    /// shared registry; also records forecast samples and the usage report.
+    Shared,
+    /// Filtered scrape or probe into a fresh registry; state kept across
+    /// collections is only read, so partial or irregular collections never
+    /// feed into it.
+    Fresh,
+    /// Unfiltered probe into a fresh registry. As a full collection it records
+    /// forecast samples, so targets served only through `/probe` get forecasts;
+    /// the shared registry and usage report are left alone.
+    Probe,
+}
+
+/// Collect all enabled collectors from PBS into the shared registry.
 pub(super) async fn collect(collector: &MetricsCollector) -> Result<()> {
+    collect_into(
+        collector,
+        collector.metrics(),
+        collector.enabled_collectors(),
+        Scope::Shared,
+    )
+    .await
+}
+
+/// Collect the given collectors from PBS into `metrics`.
+///
+/// All API data is fetched first; the registry is then reset and repopulated
+/// while holding its update lock, so an `encode()` never observes a partially
+/// populated registry.
+pub(super) async fn collect_into(
+    collector: &MetricsCollector,
+    metrics: &MetricRegistry,
+    enabled: &[Collector],
+    scope: Scope,
+) -> Result<()> {
     info!("Collecting metrics from PBS");
     let start = Instant::now();
 
-    let result = match collect_internal(collector).await {
-        Ok(_) => {
-            collector.metrics().pbs_up.set(1.0);
+    let mut data = fetch_all(collector.client(), &collector.backup_filter, enabled).await;
+    let reachable = std::mem::replace(&mut data.reachable, Ok(()));
+
+    // In background mode a collection that gets no answer from PBS at all keeps
+    // the data of the last successful one, so cached results outlive an outage.
+    // Otherwise the registry reflects exactly what was fetched.
+    let keep_cached = reachable.is_err()
+        && scope == Scope::Shared
+        && collector.collection_interval().is_some()
+        && !data.all_outcomes().any(|outcome| outcome.success);
+
+    let _update = metrics.update_guard();
+
+    if !keep_cached {
+        reset_metrics(metrics);
+        apply(
+            metrics,
+            &data,
+            collector.snapshot_history_limit,
+            collector.backup_filter(),
+            collector.freshness_rules(),
+            collector.retention_rules(),
+        );
+        apply_usage(collector, metrics, &data, enabled, scope);
+    } else {
+        // Only the outcomes of this attempt are replaced
+        metrics.collector_success.reset();
+        metrics.collector_duration_seconds.reset();
+    }
+    apply_outcomes(metrics, &data);
+
+    // pbs_up tracks whether PBS itself answers; individual collector failures
+    // are reported through pbs_collector_success instead.
+    let result = match reachable {
+        Ok(()) => {
+            metrics.pbs_up.set(1.0);
             info!("Successfully collected metrics");
             Ok(())
         }
         Err(e) => {
             error!("Failed to collect metrics: {}", e);
-            collector.metrics().pbs_up.set(0.0);
+            metrics.pbs_up.set(0.0);
             Err(e)
         }
     };
 
     // Update scrape duration
     let duration = start.elapsed().as_secs_f64();
-    collector
-        .metrics()
-        .exporter_scrape_duration_seconds
-        .set(duration);
+    metrics.exporter_scrape_duration_seconds.set(duration);
 
     // Update memory usage (Linux only)
     if let Ok(memory_bytes) = get_memory_usage() {
-        collector
-            .metrics()
-            .exporter_memory_usage_bytes
-            .set(memory_bytes as f64);
+        metrics.exporter_memory_usage_bytes.set(memory_bytes as f64);
+    }
+
+    // Failed collections leave the timestamp alone so staleness keeps growing
+    // while PBS is unreachable.
+    let now = unix_now();
+    metrics
+        .exporter_last_collection_attempt_timestamp_seconds
+        .set(now);
+    if result.is_ok() {
+        metrics.exporter_last_collection_timestamp_seconds.set(now);
     }
 
     result
 }
 
+/// Update the usage and forecast metrics, and the usage report.
+///
+/// The stored usage report is only replaced when the snapshots of every
+/// datastore were listed, so a failed or partial listing never drops groups
+/// from it.
+fn apply_usage(
+    collector: &MetricsCollector,
+    metrics: &MetricRegistry,
+    data: &CollectedData,
+    enabled: &[Collector],
+    scope: Scope,
+) {
+    let usage_rows: Vec<UsageRow> = if enabled.contains(&Collector::Snapshots) {
+        let now = unix_now() as i64;
+        data.datastore_data
+            .iter()
+            .flat_map(|ds| {
+                super::report::usage_rows(
+                    &ds.store,
+                    &ds.snapshots,
+                    collector.backup_filter(),
+                    collector.usage_growth_days(),
+                    now,
+                )
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+    super::updates::update_usage_metrics(metrics, &usage_rows);
+    if scope == Scope::Shared && data.snapshots_complete(enabled) {
+        collector.store_usage_report(usage_rows);
+    }
+
+    if let (Some(history), Some(datastores)) = (collector.usage_history(), &data.datastores) {
+        let now = unix_now();
+        let samples = match scope {
+            Scope::Shared | Scope::Probe => history.record(datastores, now),
+            Scope::Fresh => history.samples(datastores, now),
+        };
+        super::updates::update_forecast_metrics(metrics, datastores, &samples, now);
+    }
+}
+
+/// Periodically collect metrics in the background.
+///
+/// The first collection runs immediately; subsequent collections run every
+/// `interval`. A collection that overruns the interval delays the next tick
+/// instead of triggering a burst of catch-up collections.
+pub(super) async fn run_collection_loop(collector: &MetricsCollector, interval: Duration) {
+    info!(
+        "Starting background collection every {} seconds",
+        interval.as_secs_f64()
+    );
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+        // Errors are already logged and reflected in pbs_up.
+        let _ = collect_shared(collector).await;
+    }
+}
+
+/// Update the staleness gauge from the last collection timestamp.
+pub(super) fn update_collection_age(metrics: &MetricRegistry) {
+    let last_collection = metrics.exporter_last_collection_timestamp_seconds.get();
+    if last_collection > 0.0 {
+        metrics
+            .exporter_last_collection_age_seconds
+            .set((unix_now() - last_collection).max(0.0));
+    }
+}
+
+fn unix_now() -> f64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or(0.0)
+}
+
 /// Get current memory usage in bytes (Linux only).
 fn get_memory_usage() -> Result<u64> {
     #[cfg(target_os = "linux")]
@@ -68,115 +293,927 @@ fn get_memory_usage() -> Result<u64> {
     Ok(0)
 }
 
-pub(super) async fn collect_internal(collector: &MetricsCollector) -> Result<()> {
-    let metrics = collector.metrics();
-    let client = collector.client();
+/// A unit of collection that reports its own success and duration and can be
+/// enabled or disabled independently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Collector {
+    /// Host metrics from the node status endpoint
+    Node,
+    /// Datastore capacity metrics
+    Datastore,
+    /// Per-datastore status (group/snapshot counts per backup type)
+    DatastoreStatus,
+    /// Individual snapshot metrics
+    Snapshots,
+    /// Backup group metrics (snapshot count, last backup)
+    Groups,
+    /// Task metrics
+    Tasks,
+    /// Garbage collection metrics
+    Gc,
+    /// Sync job metrics
+    SyncJobs,
+    /// Verification job metrics
+    VerifyJobs,
+    /// Prune job metrics
+    PruneJobs,
+    /// Tape drives and changers, tape backup jobs, media pools and media inventory
+    Tape,
+    /// PBS version information
+    Version,
+}
 
-    reset_metrics(metrics);
+impl Collector {
+    /// Every collector, in collection order.
+    pub const ALL: [Collector; 12] = [
+        Collector::Node,
+        Collector::Datastore,
+        Collector::DatastoreStatus,
+        Collector::Snapshots,
+        Collector::Groups,
+        Collector::Tasks,
+        Collector::Gc,
+        Collector::SyncJobs,
+        Collector::VerifyJobs,
+        Collector::PruneJobs,
+        Collector::Tape,
+        Collector::Version,
+    ];
 
-    // Collect node status
-    let node_status = client.get_node_status().await?;
-    super::updates::update_node_metrics(metrics, &node_status);
+    /// Value of the `collector` label and of the `collect[]` query parameter.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Collector::Node => "node",
+            Collector::Datastore => "datastore",
+            Collector::DatastoreStatus => "datastore_status",
+            Collector::Snapshots => "snapshots",
+            Collector::Groups => "groups",
+            Collector::Tasks => "tasks",
+            Collector::Gc => "gc",
+            Collector::SyncJobs => "sync_jobs",
+            Collector::VerifyJobs => "verify_jobs",
+            Collector::PruneJobs => "prune_jobs",
+            Collector::Tape => "tape",
+            Collector::Version => "version",
+        }
+    }
 
-    // Collect datastore usage
-    let datastores = client.get_datastore_usage().await?;
-    super::updates::update_datastore_metrics(metrics, &datastores);
+    /// Look up a collector by its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str() == name)
+    }
+}
 
-    // Map to store comments for tasks (worker_id -> comment)
-    // Pre-allocate with estimated capacity
-    let mut task_comment_map = TaskCommentMap::with_capacity(datastores.len() * 10);
+/// Success and timing of one collector run (per datastore where applicable).
+struct CollectorOutcome {
+    collector: Collector,
+    datastore: String,
+    success: bool,
+    duration: Duration,
+}
 
-    // Collect backup groups and snapshots for each datastore
-    for ds in &datastores {
-        // Fetch snapshots to get comments
-        let snapshots = match client.get_snapshots(&ds.store).await {
-            Ok(snaps) => snaps,
-            Err(e) => {
-                error!("Failed to get snapshots for {}: {}", ds.store, e);
-                Vec::new()
+/// Raw PBS API data gathered during one collection.
+///
+/// `None` entries were disabled or failed to fetch and are skipped when applying.
+struct CollectedData {
+    nodes: Vec<NodeData>,
+    datastores: Option<Vec<DatastoreUsage>>,
+    /// Whether the datastore list was fetched, even if not exposed
+    datastores_listed: bool,
+    datastore_data: Vec<DatastoreData>,
+    jobs: JobData,
+    tape: Option<TapeData>,
+    version: Option<VersionInfo>,
+    /// Whether PBS answered at all (drives `pbs_up`)
+    reachable: Result<()>,
+    outcomes: Vec<CollectorOutcome>,
+}
+
+impl CollectedData {
+    /// Outcomes of every collector, including the per-datastore ones.
+    fn all_outcomes(&self) -> impl Iterator<Item = &CollectorOutcome> {
+        self.outcomes
+            .iter()
+            .chain(self.datastore_data.iter().flat_map(|ds| &ds.outcomes))
+    }
+
+    /// Whether the snapshots of every datastore were listed successfully.
+    fn snapshots_complete(&self, enabled: &[Collector]) -> bool {
+        enabled.contains(&Collector::Snapshots)
+            && self.datastores_listed
+            && self
+                .all_outcomes()
+                .filter(|outcome| outcome.collector == Collector::Snapshots)
+                .all(|outcome| outcome.success)
+    }
+}
+
+/// Per-node API data.
+struct NodeData {
+    node: String,
+    status: Option<NodeStatus>,
+    tasks: Option<Vec<Task>>,
+}
+
+/// Configured jobs with their schedule state.
+struct JobData {
+    sync: Option<Vec<SyncJob>>,
+    verify: Option<Vec<VerificationJob>>,
+    prune: Option<Vec<PruneJob>>,
+}
+
+/// Tape API data; each list is `None` if its request failed.
+struct TapeData {
+    drives: Option<Vec<TapeDrive>>,
+    /// Live status of each idle drive that could be queried
+    drive_status: Vec<(String, TapeDriveStatus)>,
+    changers: Option<Vec<TapeChanger>>,
+    /// Element status of each changer that could be queried
+    changer_status: Vec<(String, Vec<ChangerEntry>)>,
+    backup_jobs: Option<Vec<TapeBackupJob>>,
+    media_pools: Option<Vec<MediaPool>>,
+    media: Option<Vec<TapeMedia>>,
+}
+
+/// Per-datastore API data.
+struct DatastoreData {
+    store: String,
+    status: Option<DatastoreStatus>,
+    snapshots: Vec<Snapshot>,
+    groups: Option<Vec<BackupGroup>>,
+    gc_status: Option<GcStatus>,
+    outcomes: Vec<CollectorOutcome>,
+}
+
+/// Run a request and measure how long it took.
+async fn timed<T>(request: impl Future<Output = Result<T>>) -> (Result<T>, Duration) {
+    let start = Instant::now();
+    let result = request.await;
+    (result, start.elapsed())
+}
+
+/// Run a request only if its collector is enabled.
+async fn timed_if<T>(
+    enabled: bool,
+    request: impl Future<Output = Result<T>>,
+) -> Option<(Result<T>, Duration)> {
+    if enabled {
+        Some(timed(request).await)
+    } else {
+        None
+    }
+}
+
+/// Log a failed collector and record its outcome, returning the data on success.
+///
+/// Collectors that did not run (`None`) record nothing.
+fn record<T>(
+    outcomes: &mut Vec<CollectorOutcome>,
+    collector: Collector,
+    datastore: &str,
+    fetched: Option<(Result<T>, Duration)>,
+) -> Option<T> {
+    let (result, duration) = fetched?;
+    if let Err(e) = &result {
+        log_failure(collector, datastore, e);
+    }
+
+    outcomes.push(CollectorOutcome {
+        collector,
+        datastore: datastore.to_string(),
+        success: result.is_ok(),
+        duration,
+    });
+    result.ok()
+}
+
+fn log_failure(collector: Collector, datastore: &str, e: &PbsError) {
+    if datastore.is_empty() {
+        error!("Failed to collect {}: {}", collector.as_str(), e);
+    } else {
+        error!(
+            "Failed to collect {} for {}: {}",
+            collector.as_str(),
+            datastore,
+            e
+        );
+    }
+}
+
+/// Fetch everything needed for one collection.
+///
+/// Independent API calls run concurrently; the client's request semaphore
+/// bounds how many are in flight at once. Results are returned in datastore
+/// order regardless of completion order so they are applied deterministically.
+/// Each collector fails independently.
+///
+/// The datastore list is fetched whenever a per-datastore collector is enabled,
+/// and the version endpoint is always queried because it drives `pbs_up`; their
+/// metrics are only exposed when their own collectors are enabled.
+///
+/// Datastores and namespaces excluded by `backup_filter` are not queried at all.
+async fn fetch_all(
+    client: &Arc<PbsClient>,
+    backup_filter: &Arc<BackupFilter>,
+    enabled: &[Collector],
+) -> CollectedData {
+    let is_enabled = |collector| enabled.contains(&collector);
+    let per_datastore: Vec<Collector> = [
+        Collector::DatastoreStatus,
+        Collector::Snapshots,
+        Collector::Groups,
+        Collector::Gc,
+    ]
+    .into_iter()
+    .filter(|c| is_enabled(*c))
+    .collect();
+    let needs_datastores = is_enabled(Collector::Datastore) || !per_datastore.is_empty();
+
+    let datastore_fetch = async {
+        let datastores = timed_if(needs_datastores, client.get_datastore_usage()).await;
+        let datastore_data = match &datastores {
+            Some((Ok(datastores), _)) => {
+                fetch_datastore_data(client, backup_filter, datastores, &per_datastore).await
+            }
+            _ => Vec::new(),
+        };
+        (datastores, datastore_data)
+    };
+
+    let (
+        (nodes, node_outcomes),
+        (datastores, datastore_data),
+        (jobs, job_outcomes),
+        (tape, tape_outcomes),
+        version,
+    ) = tokio::join!(
+        fetch_node_data(
+            client,
+            is_enabled(Collector::Node),
+            is_enabled(Collector::Tasks)
+        ),
+        datastore_fetch,
+        fetch_job_data(client, enabled),
+        fetch_tape_data(client, is_enabled(Collector::Tape)),
+        timed(client.get_version()),
+    );
+
+    let mut outcomes = node_outcomes;
+    outcomes.extend(job_outcomes);
+    outcomes.extend(tape_outcomes);
+    let datastores_listed = matches!(&datastores, Some((Ok(_), _)));
+    let datastores = if is_enabled(Collector::Datastore) {
+        record(&mut outcomes, Collector::Datastore, "", datastores)
+    } else {
+        if let Some((Err(e), _)) = &datastores {
+            log_failure(Collector::Datastore, "", e);
+        }
+        None
+    };
+
+    let (version, version_duration) = version;
+    let (version, reachable) = match version {
+        Ok(version) => (Some(version), Ok(())),
+        Err(e) => (None, Err(e)),
+    };
+    if is_enabled(Collector::Version) {
+        if let Err(e) = &reachable {
+            log_failure(Collector::Version, "", e);
+        }
+        outcomes.push(CollectorOutcome {
+            collector: Collector::Version,
+            datastore: String::new(),
+            success: reachable.is_ok(),
+            duration: version_duration,
+        });
+    }
+    let version = version.filter(|_| is_enabled(Collector::Version));
+
+    CollectedData {
+        nodes,
+        datastores,
+        datastores_listed,
+        datastore_data,
+        jobs,
+        tape,
+        version,
+        reachable,
+        outcomes,
+    }
+}
+
+/// Fetch the job lists of all enabled job collectors concurrently.
+async fn fetch_job_data(
+    client: &PbsClient,
+    enabled: &[Collector],
+) -> (JobData, Vec<CollectorOutcome>) {
+    let is_enabled = |collector| enabled.contains(&collector);
+    let (sync, verify, prune) = tokio::join!(
+        timed_if(is_enabled(Collector::SyncJobs), client.get_sync_jobs()),
+        timed_if(
+            is_enabled(Collector::VerifyJobs),
+            client.get_verification_jobs()
+        ),
+        timed_if(is_enabled(Collector::PruneJobs), client.get_prune_jobs()),
+    );
+
+    let mut outcomes = Vec::with_capacity(3);
+    let jobs = JobData {
+        sync: record(&mut outcomes, Collector::SyncJobs, "", sync),
+        verify: record(&mut outcomes, Collector::VerifyJobs, "", verify),
+        prune: record(&mut outcomes, Collector::PruneJobs, "", prune),
+    };
+    (jobs, outcomes)
+}
+
+/// Fetch tape drives and changers with their live status, tape backup jobs,
+/// media pools and the media inventory.
+///
+/// The tape collector reports a single outcome that succeeds only if every
+/// request succeeded; lists that were fetched are applied either way. Drives
+/// held by a task are not queried for their status, since PBS cannot open them.
+async fn fetch_tape_data(
+    client: &Arc<PbsClient>,
+    enabled: bool,
+) -> (Option<TapeData>, Vec<CollectorOutcome>) {
+    if !enabled {
+        return (None, Vec::new());
+    }
+
+    let (drives, changers, backup_jobs, media_pools, media) = tokio::join!(
+        timed(client.get_tape_drives()),
+        timed(client.get_tape_changers()),
+        timed(client.get_tape_backup_jobs()),
+        timed(client.get_media_pools()),
+        timed(client.get_tape_media()),
+    );
+
+    let mut outcomes = vec![CollectorOutcome {
+        collector: Collector::Tape,
+        datastore: String::new(),
+        success: true,
+        duration: Duration::ZERO,
+    }];
+    let drives = record_merged(&mut outcomes, Collector::Tape, "", Some(drives));
+    let changers = record_merged(&mut outcomes, Collector::Tape, "", Some(changers));
+
+    let idle_drives = drives
+        .iter()
+        .flatten()
+        .filter(|drive| drive.state.is_none())
+        .map(|drive| drive.name.clone())
+        .collect();
+    let changer_names = changers
+        .iter()
+        .flatten()
+        .map(|changer| changer.name.clone())
+        .collect();
+    let (drive_status, changer_status) = tokio::join!(
+        fetch_each(client, idle_drives, |client, drive| async move {
+            client.get_tape_drive_status(&drive).await
+        }),
+        fetch_each(client, changer_names, |client, changer| async move {
+            client.get_tape_changer_status(&changer).await
+        }),
+    );
+
+    let drive_status = record_each(&mut outcomes, Collector::Tape, drive_status);
+    let changer_status = record_each(&mut outcomes, Collector::Tape, changer_status);
+
+    let tape = TapeData {
+        drives,
+        drive_status,
+        changers,
+        changer_status,
+        backup_jobs: record_merged(&mut outcomes, Collector::Tape, "", Some(backup_jobs)),
+        media_pools: record_merged(&mut outcomes, Collector::Tape, "", Some(media_pools)),
+        media: record_merged(&mut outcomes, Collector::Tape, "", Some(media)),
+    };
+    (Some(tape), outcomes)
+}
+
+/// Run `request` for every name concurrently, returning the results in name order.
+async fn fetch_each<T, F, Fut>(
+    client: &Arc<PbsClient>,
+    names: Vec<String>,
+    request: F,
+) -> Vec<(String, (Result<T>, Duration))>
+where
+    T: Send + 'static,
+    F: Fn(Arc<PbsClient>, String) -> Fut,
+    Fut: Future<Output = Result<T>> + Send + 'static,
+{
+    let mut tasks = JoinSet::new();
+    for (index, name) in names.into_iter().enumerate() {
+        let request = request(Arc::clone(client), name.clone());
+        tasks.spawn(async move { (index, name, timed(request).await) });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) => error!("Collection task failed: {}", e),
+        }
+    }
+    results.sort_unstable_by_key(|(index, ..)| *index);
+    results
+        .into_iter()
+        .map(|(_, name, fetched)| (name, fetched))
+        .collect()
+}
+
+/// Fetch node status and tasks for every configured or discovered node.
+///
+/// Each collector reports a single outcome across all nodes: it succeeds only
+/// if node discovery and every node's request succeeded, and its duration is
+/// the sum of those requests.
+async fn fetch_node_data(
+    client: &Arc<PbsClient>,
+    collect_status: bool,
+    collect_tasks: bool,
+) -> (Vec<NodeData>, Vec<CollectorOutcome>) {
+    let collectors: Vec<Collector> = [
+        (Collector::Node, collect_status),
+        (Collector::Tasks, collect_tasks),
+    ]
+    .into_iter()
+    .filter_map(|(collector, enabled)| enabled.then_some(collector))
+    .collect();
+    if collectors.is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+
+    let (nodes, discovery_duration) = timed(client.nodes()).await;
+    let mut outcomes: Vec<CollectorOutcome> = collectors
+        .iter()
+        .map(|&collector| CollectorOutcome {
+            collector,
+            datastore: String::new(),
+            success: nodes.is_ok(),
+            duration: discovery_duration,
+        })
+        .collect();
+    let nodes = match nodes {
+        Ok(nodes) => nodes,
+        Err(e) => {
+            for &collector in &collectors {
+                log_failure(collector, "", &e);
             }
+            return (Vec::new(), outcomes);
+        }
+    };
+
+    let mut tasks = JoinSet::new();
+    for (index, node) in nodes.into_iter().enumerate() {
+        let client = Arc::clone(client);
+        tasks.spawn(async move {
+            let (status, node_tasks) = tokio::join!(
+                timed_if(collect_status, client.get_node_status(&node)),
+                timed_if(collect_tasks, client.get_tasks(&node, Some(50))),
+            );
+            (index, node, status, node_tasks)
+        });
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) => error!("Node collection task failed: {}", e),
+        }
+    }
+    results.sort_unstable_by_key(|(index, ..)| *index);
+
+    let nodes = results
+        .into_iter()
+        .map(|(_, node, status, node_tasks)| NodeData {
+            status: record_merged(&mut outcomes, Collector::Node, &node, status),
+            tasks: record_merged(&mut outcomes, Collector::Tasks, &node, node_tasks),
+            node,
+        })
+        .collect();
+    (nodes, outcomes)
+}
+
+/// Like [`record`], but folds the result into the collector's existing outcome.
+fn record_merged<T>(
+    outcomes: &mut [CollectorOutcome],
+    collector: Collector,
+    node: &str,
+    fetched: Option<(Result<T>, Duration)>,
+) -> Option<T> {
+    let mut node_outcomes = Vec::with_capacity(1);
+    let data = record(&mut node_outcomes, collector, node, fetched);
+    for outcome in node_outcomes {
+        if let Some(total) = outcomes.iter_mut().find(|o| o.collector == collector) {
+            total.success &= outcome.success;
+            total.duration += outcome.duration;
+        }
+    }
+    data
+}
+
+/// [`record_merged`] for every named result, keeping the successful ones.
+fn record_each<T>(
+    outcomes: &mut [CollectorOutcome],
+    collector: Collector,
+    results: Vec<(String, (Result<T>, Duration))>,
+) -> Vec<(String, T)> {
+    results
+        .into_iter()
+        .filter_map(|(name, fetched)| {
+            let data = record_merged(outcomes, collector, &name, Some(fetched))?;
+            Some((name, data))
+        })
+        .collect()
+}
+
+/// Fetch per-datastore data for all datastores concurrently.
+async fn fetch_datastore_data(
+    client: &Arc<PbsClient>,
+    backup_filter: &Arc<BackupFilter>,
+    datastores: &[DatastoreUsage],
+    collectors: &[Collector],
+) -> Vec<DatastoreData> {
+    if collectors.is_empty() {
+        return Vec::new();
+    }
+
+    let mut tasks = JoinSet::new();
+    for (index, ds) in datastores.iter().enumerate() {
+        // Excluded datastores only have their groups listed, so they can be
+        // counted as filtered
+        let collectors: Vec<Collector> = if backup_filter.allows_datastore(&ds.store) {
+            collectors.to_vec()
+        } else {
+            collectors
+                .iter()
+                .copied()
+                .filter(|c| *c == Collector::Groups)
+                .collect()
         };
+        if collectors.is_empty() {
+            continue;
+        }
 
-        let comment_map = build_latest_snapshot_comment_map(&snapshots);
+        let client = Arc::clone(client);
+        let backup_filter = Arc::clone(backup_filter);
+        let store = ds.store.clone();
+        tasks.spawn(async move {
+            let data = fetch_datastore(&client, &backup_filter, store, &collectors).await;
+            (index, data)
+        });
+    }
+
+    let mut results = Vec::with_capacity(datastores.len());
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(result) => results.push(result),
+            Err(e) => error!("Datastore collection task failed: {}", e),
+        }
+    }
+
+    // Restore datastore order so metrics are applied deterministically
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, data)| data).collect()
+}
+
+async fn fetch_datastore(
+    client: &Arc<PbsClient>,
+    backup_filter: &BackupFilter,
+    store: String,
+    collectors: &[Collector],
+) -> DatastoreData {
+    let is_enabled = |collector| collectors.contains(&collector);
+    let (status, gc_status, (discovery, snapshots, groups)) = tokio::join!(
+        timed_if(
+            is_enabled(Collector::DatastoreStatus),
+            client.get_datastore_status(&store)
+        ),
+        timed_if(is_enabled(Collector::Gc), client.get_gc_status(&store)),
+        fetch_namespaced(
+            client,
+            backup_filter,
+            &store,
+            is_enabled(Collector::Snapshots),
+            is_enabled(Collector::Groups)
+        ),
+    );
+
+    let mut outcomes = Vec::with_capacity(4);
+    let status = record(&mut outcomes, Collector::DatastoreStatus, &store, status);
+    // Snapshots are also used for comments; a failure only loses those
+    let snapshots = record_namespaced(
+        &mut outcomes,
+        Collector::Snapshots,
+        &store,
+        &discovery,
+        snapshots,
+    )
+    .unwrap_or_default();
+    let groups = record_namespaced(&mut outcomes, Collector::Groups, &store, &discovery, groups);
+    let gc_status = record(&mut outcomes, Collector::Gc, &store, gc_status);
+    // Fall back to the GC status embedded in the datastore status
+    let gc_status = gc_status.or_else(|| {
+        is_enabled(Collector::Gc)
+            .then(|| status.as_ref()?.gc_status.clone())
+            .flatten()
+    });
+
+    DatastoreData {
+        store,
+        status,
+        snapshots,
+        groups,
+        gc_status,
+        outcomes,
+    }
+}
+
+type NamespacedResults<T> = Option<Vec<(String, (Result<Vec<T>>, Duration))>>;
+
+/// Discover a datastore's namespaces and fetch snapshots and groups from each.
+///
+/// Returns whether discovery succeeded and how long it took alongside the
+/// per-namespace results. If discovery fails, only the root namespace is
+/// collected. Snapshots of namespaces excluded by `backup_filter` are not
+/// fetched; their groups still are, so they can be counted as filtered.
+async fn fetch_namespaced(
+    client: &Arc<PbsClient>,
+    backup_filter: &BackupFilter,
+    store: &str,
+    collect_snapshots: bool,
+    collect_groups: bool,
+) -> (
+    (bool, Duration),
+    NamespacedResults<Snapshot>,
+    NamespacedResults<BackupGroup>,
+) {
+    if !collect_snapshots && !collect_groups {
+        return ((true, Duration::ZERO), None, None);
+    }
+
+    let (namespaces, discovery_duration) = timed(client.namespaces(store)).await;
+    let (namespaces, discovered) = match namespaces {
+        Ok(namespaces) => (namespaces, true),
+        Err(e) => {
+            error!("Failed to discover namespaces for {}: {}", store, e);
+            (vec![String::new()], false)
+        }
+    };
+    let snapshot_namespaces: Vec<String> = namespaces
+        .iter()
+        .filter(|ns| backup_filter.allows_namespace(ns))
+        .cloned()
+        .collect();
+
+    let snapshot_store = store.to_string();
+    let group_store = store.to_string();
+    let (snapshots, groups) = tokio::join!(
+        async {
+            if !collect_snapshots {
+                return None;
+            }
+            Some(
+                fetch_each(client, snapshot_namespaces, move |client, ns| {
+                    let store = snapshot_store.clone();
+                    async move { client.get_snapshots_in(&store, &ns).await }
+                })
+                .await,
+            )
+        },
+        async {
+            if !collect_groups {
+                return None;
+            }
+            Some(
+                fetch_each(client, namespaces, move |client, ns| {
+                    let store = group_store.clone();
+                    async move { client.get_backup_groups_in(&store, &ns).await }
+                })
+                .await,
+            )
+        },
+    );
+
+    ((discovered, discovery_duration), snapshots, groups)
+}
+
+/// Record one outcome for a per-namespace collector and concatenate the data
+/// of its namespaces.
+///
+/// The collector succeeds only if namespace discovery and every namespace
+/// succeeded; namespaces that were fetched are kept either way. Collectors
+/// that did not run (`None`) record nothing.
+fn record_namespaced<T>(
+    outcomes: &mut Vec<CollectorOutcome>,
+    collector: Collector,
+    store: &str,
+    discovery: &(bool, Duration),
+    fetched: NamespacedResults<T>,
+) -> Option<Vec<T>> {
+    let fetched = fetched?;
+    let (mut success, mut duration) = *discovery;
+    let mut data = Vec::new();
+    for (ns, (result, elapsed)) in fetched {
+        duration += elapsed;
+        match result {
+            Ok(items) => data.extend(items),
+            Err(e) => {
+                success = false;
+                let location = if ns.is_empty() {
+                    store.to_string()
+                } else {
+                    format!("{} namespace {}", store, ns)
+                };
+                log_failure(collector, &location, &e);
+            }
+        }
+    }
+
+    outcomes.push(CollectorOutcome {
+        collector,
+        datastore: store.to_string(),
+        success,
+        duration,
+    });
+    Some(data)
+}
+
+fn apply(
+    metrics: &MetricRegistry,
+    data: &CollectedData,
+    snapshot_history_limit: usize,
+    backup_filter: &BackupFilter,
+    freshness_rules: &FreshnessRules,
+    retention_rules: &RetentionRules,
+) {
+    for node in &data.nodes {
+        if let Some(status) = &node.status {
+            super::updates::update_node_metrics(metrics, &node.node, status);
+        }
+    }
+    if let Some(datastores) = &data.datastores {
+        super::updates::update_datastore_metrics(metrics, datastores);
+    }
+
+    // Map to store comments for tasks (worker_id -> comment)
+    // Pre-allocate with estimated capacity
+    let mut task_comment_map = TaskCommentMap::with_capacity(data.datastore_data.len() * 10);
+
+    for ds in &data.datastore_data {
+        if let Some(status) = &ds.status {
+            super::updates::update_datastore_status_metrics(metrics, &ds.store, status);
+        }
+
+        let comment_map = build_latest_snapshot_comment_map(&ds.snapshots);
         add_task_comments_from_snapshots(&ds.store, &comment_map, &mut task_comment_map);
 
         // Update individual snapshot metrics
         super::updates::update_snapshot_metrics(
             metrics,
             &ds.store,
-            &snapshots,
+            &ds.snapshots,
             &comment_map,
-            collector.snapshot_history_limit,
+            snapshot_history_limit,
+            backup_filter,
         );
+        super::updates::update_snapshot_file_metrics(
+            metrics,
+            &ds.store,
+            &ds.snapshots,
+            backup_filter,
+        );
+        super::updates::update_owner_metrics(metrics, &ds.store, &ds.snapshots, backup_filter);
 
-        // Fetch backup groups
-        match client.get_backup_groups(&ds.store).await {
-            Ok(groups) => {
-                super::updates::update_backup_metrics(metrics, &ds.store, &groups, &comment_map)
-            }
-            Err(e) => {
-                error!("Failed to get backup groups for {}: {}", ds.store, e);
-                // Continue with other datastores
+        if !retention_rules.is_empty() {
+            super::updates::update_retention_metrics(
+                metrics,
+                &ds.store,
+                &ds.snapshots,
+                backup_filter,
+                retention_rules,
+                unix_now() as i64,
+            );
+        }
+
+        if let Some(groups) = &ds.groups {
+            super::updates::update_backup_metrics(
+                metrics,
+                &ds.store,
+                groups,
+                &comment_map,
+                backup_filter,
+            );
+            if !freshness_rules.is_empty() {
+                super::updates::update_freshness_metrics(
+                    metrics,
+                    &ds.store,
+                    groups,
+                    backup_filter,
+                    freshness_rules,
+                    unix_now(),
+                );
             }
         }
-    }
 
-    // Collect tasks
-    match client.get_tasks(Some(50)).await {
-        Ok(tasks) => super::updates::update_task_metrics(metrics, &tasks, &task_comment_map),
-        Err(e) => {
-            error!("Failed to get tasks: {}", e);
+        if let Some(gc_status) = &ds.gc_status {
+            super::updates::update_gc_metrics(metrics, &ds.store, gc_status);
         }
     }
 
-    // Collect GC status for each datastore
-    for ds in &datastores {
-        match client.get_gc_status(&ds.store).await {
-            Ok(gc_status) => super::updates::update_gc_metrics(metrics, &ds.store, &gc_status),
-            Err(e) => {
-                error!("Failed to get GC status for {}: {}", ds.store, e);
-            }
+    for node in &data.nodes {
+        if let Some(tasks) = &node.tasks {
+            super::updates::update_task_metrics(metrics, &node.node, tasks, &task_comment_map);
         }
     }
 
-    // Collect tape drives
-    match client.get_tape_drives().await {
-        Ok(drives) => super::updates::update_tape_metrics(metrics, &drives),
-        Err(e) => {
-            error!("Failed to get tape drives: {}", e);
+    if let Some(jobs) = &data.jobs.sync {
+        super::updates::update_sync_job_metrics(metrics, jobs);
+    }
+    if let Some(jobs) = &data.jobs.verify {
+        super::updates::update_verify_job_metrics(metrics, jobs);
+    }
+    if let Some(jobs) = &data.jobs.prune {
+        super::updates::update_prune_job_metrics(metrics, jobs);
+    }
+
+    if let Some(tape) = &data.tape {
+        if let Some(drives) = &tape.drives {
+            super::updates::update_tape_metrics(metrics, drives);
+            super::updates::update_tape_drive_state_metrics(
+                metrics,
+                drives,
+                &tape.drive_status,
+                &tape.changer_status,
+            );
+        }
+        if let Some(changers) = &tape.changers {
+            super::updates::update_tape_changer_metrics(metrics, changers, &tape.changer_status);
+        }
+        if let Some(jobs) = &tape.backup_jobs {
+            super::updates::update_tape_backup_job_metrics(metrics, jobs);
+        }
+        // Pools first: they initialize the per-pool aggregates the media add to
+        if let Some(pools) = &tape.media_pools {
+            super::updates::update_media_pool_metrics(metrics, pools);
+        }
+        if let Some(media) = &tape.media {
+            super::updates::update_tape_media_metrics(metrics, media);
         }
     }
 
-    // Collect version info
-    let version = client.get_version().await?;
-    super::updates::update_version_metrics(metrics, &version);
+    if let Some(version) = &data.version {
+        super::updates::update_version_metrics(metrics, version);
+    }
+}
 
-    Ok(())
+/// Update `pbs_collector_success` and `pbs_collector_duration_seconds`.
+fn apply_outcomes(metrics: &MetricRegistry, data: &CollectedData) {
+    super::updates::update_collector_metrics(
+        metrics,
+        data.all_outcomes().map(|o| {
+            (
+                o.collector.as_str(),
+                o.datastore.as_str(),
+                o.success,
+                o.duration,
+            )
+        }),
+    );
 }
 
 fn reset_metrics(metrics: &MetricRegistry) {
     // Dynamic labels can disappear between scrapes, so reset everything that is
     // populated from current API state before collecting fresh values.
     metrics.pbs_up.set(0.0); // Will be set to 1.0 on success
-    metrics.host_cpu_usage.set(0.0);
-    metrics.host_io_wait.set(0.0);
-    metrics.host_load1.set(0.0);
-    metrics.host_load5.set(0.0);
-    metrics.host_load15.set(0.0);
-    metrics.host_memory_used_bytes.set(0.0);
-    metrics.host_memory_total_bytes.set(0.0);
-    metrics.host_memory_free_bytes.set(0.0);
-    metrics.host_swap_used_bytes.set(0.0);
-    metrics.host_swap_total_bytes.set(0.0);
-    metrics.host_swap_free_bytes.set(0.0);
-    metrics.host_rootfs_used_bytes.set(0.0);
-    metrics.host_rootfs_total_bytes.set(0.0);
-    metrics.host_rootfs_avail_bytes.set(0.0);
-    metrics.host_uptime_seconds.set(0.0);
+    metrics.host_cpu_usage.reset();
+    metrics.host_io_wait.reset();
+    metrics.host_load1.reset();
+    metrics.host_load5.reset();
+    metrics.host_load15.reset();
+    metrics.host_memory_used_bytes.reset();
+    metrics.host_memory_total_bytes.reset();
+    metrics.host_memory_free_bytes.reset();
+    metrics.host_swap_used_bytes.reset();
+    metrics.host_swap_total_bytes.reset();
+    metrics.host_swap_free_bytes.reset();
+    metrics.host_rootfs_used_bytes.reset();
+    metrics.host_rootfs_total_bytes.reset();
+    metrics.host_rootfs_avail_bytes.reset();
+    metrics.host_uptime_seconds.reset();
 
     metrics.datastore_total_bytes.reset();
     metrics.datastore_used_bytes.reset();
     metrics.datastore_available_bytes.reset();
+    metrics.datastore_estimated_full_timestamp_seconds.reset();
+    metrics.datastore_usage_history_ratio.reset();
+    metrics.datastore_growth_bytes_per_day.reset();
+    metrics.datastore_predicted_full_timestamp_seconds.reset();
+    metrics.datastore_forecast_r_squared.reset();
+    metrics.datastore_groups.reset();
+    metrics.datastore_snapshots.reset();
 
     metrics.snapshot_count.reset();
     metrics.snapshot_info.reset();
@@ -185,6 +1222,19 @@ fn reset_metrics(metrics: &MetricRegistry) {
     metrics.snapshot_verification_timestamp.reset();
     metrics.snapshot_protected.reset();
     metrics.snapshot_last_timestamp_seconds.reset();
+    metrics.backup_groups_filtered.reset();
+    metrics.backup_group_age_seconds.reset();
+    metrics.backup_group_max_age_seconds.reset();
+    metrics.backup_group_stale.reset();
+    metrics.retention_expected_buckets.reset();
+    metrics.retention_missing_buckets.reset();
+    metrics.backup_group_encrypted.reset();
+    metrics.backup_group_archive_size_bytes.reset();
+    metrics.usage_logical_bytes.reset();
+    metrics.usage_latest_snapshot_bytes.reset();
+    metrics.usage_growth_bytes.reset();
+    metrics.owner_snapshot_count.reset();
+    metrics.owner_bytes.reset();
 
     metrics.task_total.reset();
     metrics.task_duration_seconds.reset();
@@ -196,18 +1246,61 @@ fn reset_metrics(metrics: &MetricRegistry) {
     metrics.gc_removed_bytes.reset();
     metrics.gc_pending_bytes.reset();
     metrics.gc_status.reset();
+    metrics.gc_index_data_bytes.reset();
+    metrics.gc_disk_bytes.reset();
+    metrics.gc_disk_chunks.reset();
+    metrics.gc_index_file_count.reset();
+    metrics.gc_removed_chunks.reset();
+    metrics.datastore_deduplication_ratio.reset();
+
+    metrics.sync_job_info.reset();
+    metrics.sync_jobs.reset();
+
+    metrics.verify_job_info.reset();
+    metrics.verify_job_outdated_after_seconds.reset();
+    metrics.verify_job_ignore_verified.reset();
+    metrics.verify_jobs.reset();
+
+    metrics.prune_job_info.reset();
+    metrics.prune_job_keep.reset();
+    metrics.prune_jobs.reset();
 
     metrics.tape_drive_info.reset();
     metrics.tape_drive_available.set(0.0);
+    metrics.tape_drive_busy.reset();
+    metrics.tape_drive_activity.reset();
+    metrics.tape_drive_media_loaded.reset();
+    metrics.tape_drive_compression.reset();
+    metrics.tape_drive_write_protected.reset();
+    metrics.tape_drive_alert.reset();
+    metrics.tape_changer_info.reset();
+    metrics.tape_changer_slots.reset();
+    metrics.tape_changer_slots_occupied.reset();
+    metrics.tape_backup_job_info.reset();
+    metrics.tape_backup_jobs.reset();
+    metrics.tape_media_pool_info.reset();
+    metrics.tape_media_pool_encrypted.reset();
+    metrics.tape_media_pool_media.reset();
+    metrics.tape_media_pool_bytes_used.reset();
+    metrics.tape_media_info.reset();
+    metrics.tape_media_expired.reset();
+    metrics.tape_media_bytes_used.reset();
 
     metrics.pbs_version.reset();
+
+    metrics.collector_success.reset();
+    metrics.collector_duration_seconds.reset();
 }
 
 fn build_latest_snapshot_comment_map(snapshots: &[Snapshot]) -> LatestSnapshotCommentMap {
     let mut comment_map = LatestSnapshotCommentMap::with_capacity(snapshots.len() / 5);
 
     for snapshot in snapshots {
-        let key = (snapshot.backup_type.clone(), snapshot.backup_id.clone());
+        let key = (
+            snapshot.ns.clone().unwrap_or_default(),
+            snapshot.backup_type.clone(),
+            snapshot.backup_id.clone(),
+        );
 
         match comment_map.entry(key) {
             Entry::Occupied(mut entry) => {
@@ -231,10 +1324,14 @@ fn add_task_comments_from_snapshots(
     comment_map: &LatestSnapshotCommentMap,
     task_comment_map: &mut TaskCommentMap,
 ) {
-    for ((backup_type, backup_id), (_, comment)) in comment_map {
+    for ((ns, backup_type, backup_id), (_, comment)) in comment_map {
         if let Some(c) = comment {
             if !c.is_empty() {
-                let worker_id = format!("{}:{}/{}", datastore, backup_type, backup_id);
+                let worker_id = if ns.is_empty() {
+                    format!("{}:{}/{}", datastore, backup_type, backup_id)
+                } else {
+                    format!("{}:{}/{}/{}", datastore, ns, backup_type, backup_id)
+                };
                 task_comment_map.insert(worker_id, c.clone());
             }
         }
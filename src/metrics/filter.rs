@@ -0,0 +1,232 @@
+//! Include/exclude filters selecting which backup groups are exposed.
+
+use crate::config::{FilterRule, FiltersConfig};
+use crate::error::{PbsError, Result};
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+
+/// Prefix marking a filter pattern as a regular expression instead of a glob.
+const REGEX_PREFIX: &str = "re:";
+
+/// One compiled filter pattern: a glob, or a regex when prefixed with `re:`.
+enum Pattern {
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+impl Pattern {
+    fn new(pattern: &str) -> std::result::Result<Self, String> {
+        match pattern.strip_prefix(REGEX_PREFIX) {
+            Some(regex) => Regex::new(regex)
+                .map(Pattern::Regex)
+                .map_err(|e| e.to_string()),
+            None => Glob::new(pattern)
+                .map(|glob| Pattern::Glob(glob.compile_matcher()))
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    fn is_match(&self, value: &str) -> bool {
+        match self {
+            Pattern::Glob(glob) => glob.is_match(value),
+            Pattern::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// Backup group selector of a freshness or retention rule.
+///
+/// `group` is matched against `<backup_type>/<backup_id>`; the optional
+/// datastore and namespace patterns narrow the selection.
+pub(super) struct GroupPattern {
+    group: Pattern,
+    datastore: Option<Pattern>,
+    namespace: Option<Pattern>,
+}
+
+impl GroupPattern {
+    pub(super) fn new(
+        group: &str,
+        datastore: Option<&str>,
+        namespace: Option<&str>,
+    ) -> std::result::Result<Self, String> {
+        Ok(Self {
+            group: Pattern::new(group)?,
+            datastore: datastore.map(Pattern::new).transpose()?,
+            namespace: namespace.map(Pattern::new).transpose()?,
+        })
+    }
+
+    /// Whether a backup group is selected; `namespace` is empty for the root.
+    pub(super) fn matches(
+        &self,
+        datastore: &str,
+        namespace: &str,
+        backup_type: &str,
+        backup_id: &str,
+    ) -> bool {
+        self.group
+            .is_match(&format!("{}/{}", backup_type, backup_id))
+            && self
+                .datastore
+                .as_ref()
+                .is_none_or(|p| p.is_match(datastore))
+            && self
+                .namespace
+                .as_ref()
+                .is_none_or(|p| p.is_match(namespace))
+    }
+}
+
+/// Compiled [`FilterRule`].
+#[derive(Default)]
+struct Rule {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl Rule {
+    /// Compile a rule; `field` names it in error messages.
+    fn new(rule: &FilterRule, field: &str) -> Result<Self> {
+        let compile = |patterns: &[String], list: &str| {
+            patterns
+                .iter()
+                .map(|pattern| {
+                    Pattern::new(pattern).map_err(|e| {
+                        PbsError::InvalidRule(format!(
+                            "filters.{}.{} pattern '{}': {}",
+                            field, list, pattern, e
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+
+        Ok(Self {
+            include: compile(&rule.include, "include")?,
+            exclude: compile(&rule.exclude, "exclude")?,
+        })
+    }
+
+    fn allows(&self, value: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.is_match(value)))
+            && !self.exclude.iter().any(|p| p.is_match(value))
+    }
+}
+
+/// Compiled `[filters]` configuration.
+///
+/// A backup group is exposed only if its datastore, namespace, backup type and
+/// backup ID all pass their rules. The default filter allows everything.
+#[derive(Default)]
+pub(crate) struct BackupFilter {
+    datastores: Rule,
+    namespaces: Rule,
+    backup_types: Rule,
+    backup_ids: Rule,
+}
+
+impl BackupFilter {
+    /// Compile the configured patterns.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PbsError::InvalidRule`] naming the first invalid glob or regex.
+    pub(crate) fn new(config: &FiltersConfig) -> Result<Self> {
+        Ok(Self {
+            datastores: Rule::new(&config.datastores, "datastores")?,
+            namespaces: Rule::new(&config.namespaces, "namespaces")?,
+            backup_types: Rule::new(&config.backup_types, "backup_types")?,
+            backup_ids: Rule::new(&config.backup_ids, "backup_ids")?,
+        })
+    }
+
+    /// Whether any backup group of a datastore can be exposed.
+    pub(crate) fn allows_datastore(&self, datastore: &str) -> bool {
+        self.datastores.allows(datastore)
+    }
+
+    /// Whether any backup group of a namespace can be exposed; `namespace` is
+    /// empty for the root.
+    pub(crate) fn allows_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.allows(namespace)
+    }
+
+    /// Whether a backup group is exposed; `namespace` is empty for the root.
+    pub(crate) fn allows_group(
+        &self,
+        datastore: &str,
+        namespace: &str,
+        backup_type: &str,
+        backup_id: &str,
+    ) -> bool {
+        self.allows_datastore(datastore)
+            && self.allows_namespace(namespace)
+            && self.backup_types.allows(backup_type)
+            && self.backup_ids.allows(backup_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(include: &[&str], exclude: &[&str]) -> FilterRule {
+        FilterRule {
+            include: include.iter().map(|p| p.to_string()).collect(),
+            exclude: exclude.iter().map(|p| p.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_default_filter_allows_everything() {
+        let filter = BackupFilter::new(&FiltersConfig::default()).unwrap();
+        assert!(filter.allows_group("backup", "", "vm", "100"));
+        assert!(filter.allows_group("backup", "prod/db", "host", "web"));
+    }
+
+    #[test]
+    fn test_globs_and_regexes_include_and_exclude() {
+        let filter = BackupFilter::new(&FiltersConfig {
+            datastores: rule(&["prod-*"], &[]),
+            backup_ids: rule(&[], &["test-*", r"re:^tmp-\d+$"]),
+            ..FiltersConfig::default()
+        })
+        .unwrap();
+
+        assert!(filter.allows_group("prod-1", "", "vm", "web"));
+        assert!(!filter.allows_group("dev-1", "", "vm", "web"));
+        assert!(!filter.allows_group("prod-1", "", "vm", "test-web"));
+        assert!(!filter.allows_group("prod-1", "", "vm", "tmp-42"));
+        // Regexes are not anchored implicitly; globs always match the whole value
+        assert!(filter.allows_group("prod-1", "", "vm", "tmp-42a"));
+        assert!(filter.allows_group("prod-1", "", "vm", "my-test-web"));
+    }
+
+    #[test]
+    fn test_datastore_and_namespace_rules_apply_on_their_own() {
+        let filter = BackupFilter::new(&FiltersConfig {
+            datastores: rule(&[], &["scratch"]),
+            namespaces: rule(&["", "prod/*"], &[]),
+            backup_ids: rule(&[], &["test-*"]),
+            ..FiltersConfig::default()
+        })
+        .unwrap();
+
+        assert!(filter.allows_datastore("backup"));
+        assert!(!filter.allows_datastore("scratch"));
+        assert!(filter.allows_namespace(""));
+        assert!(filter.allows_namespace("prod/db"));
+        assert!(!filter.allows_namespace("dev"));
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_an_invalid_rule_error() {
+        let result = BackupFilter::new(&FiltersConfig {
+            namespaces: rule(&["re:("], &[]),
+            ..FiltersConfig::default()
+        });
+        let message = result.err().unwrap().to_string();
+        assert!(message.contains("filters.namespaces.include"));
+    }
+}
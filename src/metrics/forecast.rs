@@ -0,0 +1,126 @@
+//! Rolling datastore usage samples kept across collections for fill-rate forecasting.
+
+use crate::client::DatastoreUsage;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// Upper bound of samples kept per datastore; samples closer together than
+/// `window / MAX_SAMPLES` are skipped so short collection intervals stay cheap.
+const MAX_SAMPLES: usize = 1024;
+
+/// One datastore usage observation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct UsageSample {
+    /// Unix timestamp in seconds
+    pub(super) timestamp: f64,
+    /// Used bytes
+    pub(super) used: f64,
+}
+
+/// Per-datastore usage samples within a rolling time window.
+///
+/// The window is seeded from the usage history PBS reports alongside the
+/// datastore usage, so forecasts are available right after an exporter restart.
+pub(super) struct UsageHistory {
+    window_seconds: f64,
+    samples: Mutex<HashMap<String, VecDeque<UsageSample>>>,
+}
+
+impl UsageHistory {
+    pub(super) fn new(window: Duration) -> Self {
+        Self {
+            window_seconds: window.as_secs_f64(),
+            samples: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record the current usage of every datastore and return each
+    /// datastore's samples within the window, oldest first.
+    ///
+    /// Datastores that are no longer reported are forgotten.
+    pub(super) fn record(
+        &self,
+        datastores: &[DatastoreUsage],
+        now: f64,
+    ) -> HashMap<String, Vec<UsageSample>> {
+        let oldest = now - self.window_seconds;
+        let min_spacing = self.window_seconds / MAX_SAMPLES as f64;
+
+        let mut samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+        samples.retain(|store, _| datastores.iter().any(|ds| &ds.store == store));
+
+        datastores
+            .iter()
+            .map(|ds| {
+                let store_samples = samples.entry(ds.store.clone()).or_insert_with(|| {
+                    let mut seeded = VecDeque::new();
+                    seeded.extend(seed_from_pbs_history(ds).filter(|s| s.timestamp >= oldest));
+                    seeded
+                });
+
+                let due = store_samples
+                    .back()
+                    .is_none_or(|last| now - last.timestamp >= min_spacing);
+                if due {
+                    store_samples.push_back(UsageSample {
+                        timestamp: now,
+                        used: ds.used as f64,
+                    });
+                }
+                while store_samples
+                    .front()
+                    .is_some_and(|sample| sample.timestamp < oldest)
+                {
+                    store_samples.pop_front();
+                }
+
+                (ds.store.clone(), store_samples.iter().copied().collect())
+            })
+            .collect()
+    }
+
+    /// Each known datastore's samples within the window, oldest first,
+    /// without recording a new sample.
+    pub(super) fn samples(
+        &self,
+        datastores: &[DatastoreUsage],
+        now: f64,
+    ) -> HashMap<String, Vec<UsageSample>> {
+        let oldest = now - self.window_seconds;
+        let samples = self.samples.lock().unwrap_or_else(PoisonError::into_inner);
+
+        datastores
+            .iter()
+            .filter_map(|ds| {
+                let store_samples = samples.get(&ds.store)?;
+                let in_window = store_samples
+                    .iter()
+                    .filter(|sample| sample.timestamp >= oldest)
+                    .copied()
+                    .collect();
+                Some((ds.store.clone(), in_window))
+            })
+            .collect()
+    }
+}
+
+/// Convert the PBS usage history (fractions of the total size) into samples.
+fn seed_from_pbs_history(ds: &DatastoreUsage) -> impl Iterator<Item = UsageSample> + '_ {
+    let start = ds.history_start.unwrap_or_default();
+    let delta = ds.history_delta.unwrap_or_default();
+    let history = match &ds.history {
+        Some(history) if delta > 0 => history.as_slice(),
+        _ => &[],
+    };
+
+    history
+        .iter()
+        .enumerate()
+        .filter_map(move |(index, ratio)| {
+            ratio.map(|ratio| UsageSample {
+                timestamp: (start + index as i64 * delta) as f64,
+                used: ratio * ds.total as f64,
+            })
+        })
+}
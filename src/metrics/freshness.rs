@@ -0,0 +1,129 @@
+//! Per-group backup freshness SLOs evaluated against the latest backup time.
+
+use super::filter::GroupPattern;
+use crate::config::FreshnessRule;
+use crate::error::{PbsError, Result};
+
+/// Compiled [`FreshnessRule`].
+struct Rule {
+    groups: GroupPattern,
+    max_age_seconds: f64,
+}
+
+/// Compiled `[[freshness]]` rules, evaluated in configuration order.
+#[derive(Default)]
+pub(crate) struct FreshnessRules {
+    rules: Vec<Rule>,
+}
+
+impl FreshnessRules {
+    /// Compile the configured rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PbsError::InvalidRule`] for an invalid pattern or a maximum age
+    /// that is not a positive number of hours.
+    pub(crate) fn new(rules: &[FreshnessRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let invalid = |message: String| {
+                    PbsError::InvalidRule(format!(
+                        "freshness rule {} ({}): {}",
+                        index + 1,
+                        rule.group,
+                        message
+                    ))
+                };
+
+                if !(rule.max_age_hours.is_finite() && rule.max_age_hours > 0.0) {
+                    return Err(invalid("max_age_hours must be positive".to_string()));
+                }
+
+                Ok(Rule {
+                    groups: GroupPattern::new(
+                        &rule.group,
+                        rule.datastore.as_deref(),
+                        rule.namespace.as_deref(),
+                    )
+                    .map_err(invalid)?,
+                    max_age_seconds: rule.max_age_hours * 3600.0,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Whether no rules are configured.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Maximum backup age of a group from the first matching rule.
+    pub(crate) fn max_age_seconds(
+        &self,
+        datastore: &str,
+        namespace: &str,
+        backup_type: &str,
+        backup_id: &str,
+    ) -> Option<f64> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.groups
+                    .matches(datastore, namespace, backup_type, backup_id)
+            })
+            .map(|rule| rule.max_age_seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(group: &str, namespace: Option<&str>, max_age_hours: f64) -> FreshnessRule {
+        FreshnessRule {
+            group: group.to_string(),
+            datastore: None,
+            namespace: namespace.map(str::to_string),
+            max_age_hours,
+        }
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let rules = FreshnessRules::new(&[
+            rule("host/db-*", None, 2.0),
+            rule("vm/*", Some("prod*"), 26.0),
+            rule("*", None, 168.0),
+        ])
+        .unwrap();
+
+        assert_eq!(
+            rules.max_age_seconds("backup", "", "host", "db-1"),
+            Some(7200.0)
+        );
+        assert_eq!(
+            rules.max_age_seconds("backup", "prod", "vm", "100"),
+            Some(93600.0)
+        );
+        assert_eq!(
+            rules.max_age_seconds("backup", "", "vm", "100"),
+            Some(604800.0)
+        );
+    }
+
+    #[test]
+    fn test_unmatched_group_has_no_slo() {
+        let rules = FreshnessRules::new(&[rule("vm/*", None, 26.0)]).unwrap();
+        assert_eq!(rules.max_age_seconds("backup", "", "ct", "101"), None);
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        assert!(FreshnessRules::new(&[rule("re:(", None, 1.0)]).is_err());
+        assert!(FreshnessRules::new(&[rule("vm/*", None, -1.0)]).is_err());
+    }
+}
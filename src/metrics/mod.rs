@@ -5,16 +5,46 @@
 
 // Module declarations
 mod collectors;
+mod filter;
+mod forecast;
+mod freshness;
 mod registry;
+mod report;
+mod retention;
 mod updates;
 
 // Re-exports
+pub use collectors::Collector;
 use collectors::*;
+pub(crate) use filter::BackupFilter;
+use forecast::UsageHistory;
+pub(crate) use freshness::FreshnessRules;
 pub use registry::MetricRegistry;
+pub use report::{usage_csv, UsageRow};
+pub(crate) use retention::RetentionRules;
 
 use crate::client::PbsClient;
-use crate::error::Result;
-use std::sync::Arc;
+use crate::config::{FiltersConfig, FreshnessRule, RetentionRule, DEFAULT_TARGET_NAME};
+use crate::error::{PbsError, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tracing::warn;
+
+/// Outcome of a collection, shared with every scrape that joined it.
+type CollectionOutcome = std::result::Result<(), String>;
+/// Receiver side of an in-flight collection's outcome.
+type CollectionReceiver = watch::Receiver<Option<CollectionOutcome>>;
+/// Slot holding the currently running collection, if any.
+type InFlightCollection = Mutex<Option<CollectionReceiver>>;
+/// Usage report of the last shared collection and when it was taken.
+type UsageReportCache = Mutex<Option<(Instant, Vec<UsageRow>)>>;
+
+/// How long an on-scrape collector reuses its usage report before a report
+/// request collects again.
+const USAGE_REPORT_MAX_AGE: Duration = Duration::from_secs(60);
 
 /// Metrics collector for PBS exporter.
 ///
@@ -36,6 +66,28 @@ pub struct MetricsCollector {
     /// - limit=1: Minimal cardinality, only current state
     /// - limit=7: Good balance for weekly retention monitoring
     pub snapshot_history_limit: usize,
+    /// Background collection interval (`None` = collect on every scrape).
+    collection_interval: Option<Duration>,
+    /// Collection currently in progress, joined by concurrent scrapes.
+    in_flight: Arc<InFlightCollection>,
+    /// Collectors that run during a collection.
+    enabled_collectors: Vec<Collector>,
+    /// `pbs_server` label added to every series in static multi-target mode.
+    server_label: Option<String>,
+    /// Whether backup group metrics carry an `owner` label.
+    owner_label: bool,
+    /// Datastore usage samples for fill-rate forecasting (`None` = disabled).
+    usage_history: Option<Arc<UsageHistory>>,
+    /// Backup groups exposed in snapshot and group metrics.
+    backup_filter: Arc<BackupFilter>,
+    /// Backup freshness SLOs evaluated for each backup group.
+    freshness_rules: Arc<FreshnessRules>,
+    /// Expected retention checked against each backup group's snapshots.
+    retention_rules: Arc<RetentionRules>,
+    /// Days covered by the usage report's growth column (0 = disabled).
+    usage_growth_days: u64,
+    /// Usage report of the last shared collection and when it was taken.
+    usage_report: Arc<UsageReportCache>,
 }
 
 impl MetricsCollector {
@@ -58,12 +110,10 @@ impl MetricsCollector {
     ///
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// let config = PbsConfig {
-    ///     endpoint: "https://pbs.example.com:8007".to_string(),
-    ///     token_id: "user@pam!token".to_string(),
-    ///     token_secret: "secret".to_string(),
     ///     verify_tls: true,
     ///     timeout_seconds: 30,
     ///     snapshot_history_limit: 7,
+    ///     ..PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret")
     /// };
     /// let client = PbsClient::new(config)?;
     /// let collector = MetricsCollector::new(Arc::new(client), 7)?;
@@ -72,14 +122,188 @@ impl MetricsCollector {
     /// ```
     pub fn new(client: Arc<PbsClient>, snapshot_history_limit: usize) -> Result<Self> {
         let metrics = MetricRegistry::new()?;
+        metrics.register_api_metrics(client.api_metrics())?;
 
         Ok(Self {
             client,
             metrics,
             snapshot_history_limit,
+            collection_interval: None,
+            in_flight: Arc::new(Mutex::new(None)),
+            enabled_collectors: Collector::ALL.to_vec(),
+            server_label: None,
+            owner_label: false,
+            usage_history: None,
+            backup_filter: Arc::new(BackupFilter::default()),
+            freshness_rules: Arc::new(FreshnessRules::default()),
+            retention_rules: Arc::new(RetentionRules::default()),
+            usage_growth_days: 0,
+            usage_report: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// Label every exposed series with `pbs_server="<server>"`.
+    ///
+    /// Used in static multi-target mode so that several servers can share one
+    /// `/metrics` response (see [`collect_all`]). Must be called before the
+    /// first collection, as it replaces the metric registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the label value is invalid or metric registration fails.
+    pub fn with_server_label(mut self, server: &str) -> Result<Self> {
+        self.server_label = Some(server.to_string());
+        self.metrics = self.new_registry()?;
+        Ok(self)
+    }
+
+    /// Add an `owner` label with the backup owner (user or API token) to
+    /// `pbs_snapshot_count` and `pbs_snapshot_last_timestamp_seconds`.
+    ///
+    /// Must be called before the first collection, as it replaces the metric
+    /// registry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if metric registration fails.
+    pub fn with_owner_label(mut self, enabled: bool) -> Result<Self> {
+        self.owner_label = enabled;
+        self.metrics = self.new_registry()?;
+        Ok(self)
+    }
+
+    /// Restrict collection to the given collectors (all are enabled by default).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use pbs_exporter::{client::PbsClient, config::PbsConfig, metrics::{Collector, MetricsCollector}};
+    /// # use std::sync::Arc;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
+    /// # let client = PbsClient::new(config)?;
+    /// let collector = MetricsCollector::new(Arc::new(client), 7)?
+    ///     .with_collectors([Collector::Node, Collector::Datastore]);
+    /// assert!(!collector.enabled_collectors().contains(&Collector::Tape));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_collectors(mut self, collectors: impl IntoIterator<Item = Collector>) -> Self {
+        self.enabled_collectors = collectors.into_iter().collect();
+        self
+    }
+
+    /// Collectors that run during a collection.
+    pub fn enabled_collectors(&self) -> &[Collector] {
+        &self.enabled_collectors
+    }
+
+    /// Configure background collection.
+    ///
+    /// When `interval_seconds` is non-zero, metrics are refreshed on a timer by
+    /// [`run_collection_loop`](Self::run_collection_loop) and `/metrics` serves the
+    /// last completed result. A value of 0 keeps the default on-scrape mode.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use pbs_exporter::{client::PbsClient, config::PbsConfig, metrics::MetricsCollector};
+    /// # use std::sync::Arc;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
+    /// # let client = PbsClient::new(config)?;
+    /// let collector = MetricsCollector::new(Arc::new(client), 7)?.with_collection_interval(60);
+    /// assert!(collector.collection_interval().is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_collection_interval(mut self, interval_seconds: u64) -> Self {
+        self.collection_interval =
+            (interval_seconds > 0).then(|| Duration::from_secs(interval_seconds));
+        self
+    }
+
+    /// Enable the exporter's datastore fill-rate forecast.
+    ///
+    /// Datastore usage is sampled on every collection and the last
+    /// `window_hours` of samples are fitted to expose
+    /// `pbs_datastore_growth_bytes_per_day`,
+    /// `pbs_datastore_predicted_full_timestamp_seconds` and
+    /// `pbs_datastore_forecast_r_squared`. A value of 0 disables forecasting.
+    pub fn with_forecast_window(mut self, window_hours: u64) -> Self {
+        self.usage_history = (window_hours > 0)
+            .then(|| Arc::new(UsageHistory::new(Duration::from_secs(window_hours * 3600))));
+        self
+    }
+
+    /// Expose only the backup groups passing the `[filters]` include/exclude rules.
+    ///
+    /// Filtered groups are left out of all `pbs_snapshot_*` metrics; the
+    /// `groups` collector counts them per datastore in `pbs_backup_groups_filtered`.
+    /// Snapshots of excluded datastores and namespaces are not fetched, nor are
+    /// the status and GC of excluded datastores; only their groups are listed
+    /// so they are counted as well.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a glob or regex pattern is invalid.
+    pub fn with_filters(mut self, filters: &FiltersConfig) -> Result<Self> {
+        self.backup_filter = Arc::new(BackupFilter::new(filters)?);
+        Ok(self)
+    }
+
+    /// Evaluate backup freshness SLOs for every backup group.
+    ///
+    /// Groups matched by a rule expose `pbs_backup_group_age_seconds`,
+    /// `pbs_backup_group_max_age_seconds` and `pbs_backup_group_stale`;
+    /// the first matching rule wins. Requires the `groups` collector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is invalid or a maximum age is not positive.
+    pub fn with_freshness_rules(mut self, rules: &[FreshnessRule]) -> Result<Self> {
+        self.freshness_rules = Arc::new(FreshnessRules::new(rules)?);
+        Ok(self)
+    }
+
+    /// Check every backup group's snapshots against its expected retention.
+    ///
+    /// Groups matched by a rule expose `pbs_retention_expected_buckets` and
+    /// `pbs_retention_missing_buckets` per `bucket` (hourly, daily, weekly,
+    /// monthly, yearly); the first matching rule wins. Requires the
+    /// `snapshots` collector.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pattern is invalid or a rule sets no `keep_*` count.
+    pub fn with_retention_rules(mut self, rules: &[RetentionRule]) -> Result<Self> {
+        self.retention_rules = Arc::new(RetentionRules::new(rules)?);
+        Ok(self)
+    }
+
+    /// Report how much each backup group grew over the last `days` days.
+    ///
+    /// Sets `growth_bytes` of the usage report and `pbs_usage_growth_bytes`.
+    /// A value of 0 (the default) disables growth.
+    pub fn with_usage_growth_days(mut self, days: u64) -> Self {
+        self.usage_growth_days = days;
+        self
+    }
+
+    /// Background collection interval, or `None` when collecting on scrape.
+    pub fn collection_interval(&self) -> Option<Duration> {
+        self.collection_interval
+    }
+
+    /// Collect metrics from PBS on a fixed interval, forever.
+    ///
+    /// Intended to be spawned as a background task when a collection interval
+    /// is configured. Each successful collection updates
+    /// `pbs_exporter_last_collection_timestamp_seconds`.
+    pub async fn run_collection_loop(&self, interval: Duration) {
+        run_collection_loop(self, interval).await
+    }
+
     /// Collect all metrics from PBS.
     ///
     /// This method fetches current data from PBS API and updates all metrics including:
@@ -92,10 +316,16 @@ impl MetricsCollector {
     /// - Version information
     ///
     /// The collection process also updates self-monitoring metrics like scrape duration and memory usage.
+    /// Each sub-collector fails independently and reports `pbs_collector_success` and
+    /// `pbs_collector_duration_seconds`, so one broken endpoint does not blank the rest.
+    ///
+    /// Concurrent calls are deduplicated: a call made while a collection is already
+    /// running waits for that collection and returns its result instead of starting
+    /// another walk of the PBS API.
     ///
     /// # Returns
     ///
-    /// Returns Ok(()) on successful collection, or an error if the PBS API is unreachable or returns invalid data.
+    /// Returns Ok(()) when PBS answered the collection, or an error if the PBS API is unreachable.
     /// On error, the `pbs_up` metric is set to 0.
     ///
     /// # Example
@@ -104,14 +334,7 @@ impl MetricsCollector {
     /// # use pbs_exporter::{client::PbsClient, config::PbsConfig, metrics::MetricsCollector};
     /// # use std::sync::Arc;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: true,
-    /// #     timeout_seconds: 30,
-    /// #     snapshot_history_limit: 7,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
     /// # let collector = MetricsCollector::new(Arc::new(client), 7)?;
     /// collector.collect().await?;
@@ -119,7 +342,113 @@ impl MetricsCollector {
     /// # }
     /// ```
     pub async fn collect(&self) -> Result<()> {
-        collect(self).await
+        collect_shared(self).await
+    }
+
+    /// Collect a subset of collectors into a fresh registry and encode it.
+    ///
+    /// Used for filtered scrapes (`/metrics?collect[]=snapshots`): only the requested
+    /// collectors that are also enabled run, and the shared registry (and any cached
+    /// background result) is left untouched. Collection failures are logged and
+    /// reflected in `pbs_up` and `pbs_collector_success` of the returned output.
+    ///
+    /// # Returns
+    ///
+    /// Returns the encoded metrics, or an error if the registry cannot be built or encoded.
+    pub async fn collect_fresh(&self, collectors: &[Collector]) -> Result<String> {
+        let metrics = self.new_registry()?;
+        self.collect_fresh_into(&metrics, collectors).await;
+        metrics.encode()
+    }
+
+    /// Collect all enabled collectors into a fresh registry and encode it.
+    ///
+    /// Used for unfiltered probes (`/probe?target=<name>`). Like
+    /// [`Self::collect_fresh`], the shared registry is left untouched, but as a
+    /// full collection it records datastore usage samples for the fill-rate
+    /// forecast.
+    ///
+    /// # Returns
+    ///
+    /// Returns the encoded metrics, or an error if the registry cannot be built or encoded.
+    pub async fn collect_probe(&self) -> Result<String> {
+        let metrics = self.new_registry()?;
+        self.collect_scoped(&metrics, &self.enabled_collectors, Scope::Probe)
+            .await;
+        metrics.encode()
+    }
+
+    /// Collect a subset of collectors into a fresh registry from [`Self::new_registry`].
+    async fn collect_fresh_into(&self, metrics: &MetricRegistry, collectors: &[Collector]) {
+        self.collect_scoped(metrics, collectors, Scope::Fresh).await;
+    }
+
+    /// Collect the enabled ones of `collectors` into a fresh registry.
+    async fn collect_scoped(
+        &self,
+        metrics: &MetricRegistry,
+        collectors: &[Collector],
+        scope: Scope,
+    ) {
+        let enabled: Vec<Collector> = collectors
+            .iter()
+            .copied()
+            .filter(|c| self.enabled_collectors.contains(c))
+            .collect();
+
+        // Errors are already logged and reflected in pbs_up.
+        let _ = collect_into(self, metrics, &enabled, scope).await;
+
+        update_collection_age(metrics);
+    }
+
+    /// Refresh the shared registry unless a background loop keeps it up to date.
+    async fn refreshed_registry(&self) -> MetricRegistry {
+        if self.collection_interval.is_none() {
+            if let Err(e) = self.collect().await {
+                // Still return metrics, but pbs_up will be 0
+                warn!("Failed to collect metrics: {}", e);
+            }
+        }
+        update_collection_age(&self.metrics);
+        self.metrics.clone()
+    }
+
+    /// Logical storage usage per backup group.
+    ///
+    /// Serves the report of the last full collection that listed the snapshots
+    /// of every datastore. A collection is joined or started only if there is
+    /// no report yet, or, without a background loop, if the report is older
+    /// than a minute. Filtered scrapes, probes and collections with a failed
+    /// snapshot listing never update the report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `snapshots` collector is disabled, or if no
+    /// collection has listed all snapshots yet.
+    pub async fn usage_report(&self) -> Result<Vec<UsageRow>> {
+        if !self.enabled_collectors.contains(&Collector::Snapshots) {
+            return Err(PbsError::Metrics(
+                "the snapshots collector is disabled".to_string(),
+            ));
+        }
+
+        let stale = match self.lock_usage_report().as_ref() {
+            None => true,
+            Some((taken, _)) => {
+                self.collection_interval.is_none() && taken.elapsed() > USAGE_REPORT_MAX_AGE
+            }
+        };
+        if stale {
+            if let Err(e) = self.collect().await {
+                warn!("Failed to collect metrics: {}", e);
+            }
+        }
+
+        self.lock_usage_report()
+            .as_ref()
+            .map(|(_, rows)| rows.clone())
+            .ok_or_else(|| PbsError::Collection("no complete snapshot listing yet".to_string()))
     }
 
     /// Encode metrics in Prometheus text format.
@@ -137,14 +466,7 @@ impl MetricsCollector {
     /// # use pbs_exporter::{client::PbsClient, config::PbsConfig, metrics::MetricsCollector};
     /// # use std::sync::Arc;
     /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-    /// # let config = PbsConfig {
-    /// #     endpoint: "https://pbs.example.com:8007".to_string(),
-    /// #     token_id: "user@pam!token".to_string(),
-    /// #     token_secret: "secret".to_string(),
-    /// #     verify_tls: true,
-    /// #     timeout_seconds: 30,
-    /// #     snapshot_history_limit: 7,
-    /// # };
+    /// # let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
     /// # let client = PbsClient::new(config)?;
     /// # let collector = MetricsCollector::new(Arc::new(client), 7)?;
     /// collector.collect().await?;
@@ -154,9 +476,17 @@ impl MetricsCollector {
     /// # }
     /// ```
     pub fn encode(&self) -> Result<String> {
+        update_collection_age(&self.metrics);
         self.metrics.encode()
     }
 
+    /// Create an empty registry carrying this collector's labels and API metrics.
+    fn new_registry(&self) -> Result<MetricRegistry> {
+        let metrics = MetricRegistry::with_options(self.server_label.as_deref(), self.owner_label)?;
+        metrics.register_api_metrics(self.client.api_metrics())?;
+        Ok(metrics)
+    }
+
     /// Get a reference to the client (used by submodules)
     pub(crate) fn client(&self) -> &Arc<PbsClient> {
         &self.client
@@ -166,4 +496,128 @@ impl MetricsCollector {
     pub(crate) fn metrics(&self) -> &MetricRegistry {
         &self.metrics
     }
+
+    /// Get the usage history used for forecasting (used by submodules)
+    fn usage_history(&self) -> Option<&UsageHistory> {
+        self.usage_history.as_deref()
+    }
+
+    /// Get the backup group filter (used by submodules)
+    fn backup_filter(&self) -> &BackupFilter {
+        &self.backup_filter
+    }
+
+    /// Get the backup freshness SLOs (used by submodules)
+    fn freshness_rules(&self) -> &FreshnessRules {
+        &self.freshness_rules
+    }
+
+    /// Get the expected retention rules (used by submodules)
+    fn retention_rules(&self) -> &RetentionRules {
+        &self.retention_rules
+    }
+
+    /// Get the usage report growth window in days (used by submodules)
+    fn usage_growth_days(&self) -> u64 {
+        self.usage_growth_days
+    }
+
+    /// Replace the usage report, labelling rows with the server (used by submodules)
+    fn store_usage_report(&self, mut rows: Vec<UsageRow>) {
+        for row in &mut rows {
+            row.server.clone_from(&self.server_label);
+        }
+        *self.lock_usage_report() = Some((Instant::now(), rows));
+    }
+
+    fn lock_usage_report(&self) -> MutexGuard<'_, Option<(Instant, Vec<UsageRow>)>> {
+        self.usage_report
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Get the in-flight collection slot (used by submodules)
+    pub(crate) fn in_flight(&self) -> &InFlightCollection {
+        &self.in_flight
+    }
+}
+
+/// Collect several PBS servers concurrently and encode them as one response.
+///
+/// Used in static multi-target mode. Each collector should be labelled with
+/// [`MetricsCollector::with_server_label`] so that series from different
+/// servers stay distinct. Servers are collected independently: a server that
+/// is down only reports `pbs_up 0` for its own label and never blanks out the
+/// others; neither does a server whose collection task panics, which is
+/// logged and encoded with `pbs_up 0`. With `requested` set, each server
+/// collects only those collectors into a fresh registry, as
+/// [`MetricsCollector::collect_fresh`] does.
+///
+/// # Errors
+///
+/// Returns an error if a registry cannot be built or the output cannot be encoded.
+pub async fn collect_all(
+    collectors: &[MetricsCollector],
+    requested: Option<&[Collector]>,
+) -> Result<String> {
+    let mut registries = Vec::with_capacity(collectors.len());
+    let mut tasks = JoinSet::new();
+    let mut task_indices = HashMap::new();
+    for (index, collector) in collectors.iter().cloned().enumerate() {
+        let registry = match requested {
+            Some(_) => collector.new_registry()?,
+            None => collector.metrics.clone(),
+        };
+        registries.push(registry.clone());
+
+        let requested = requested.map(<[Collector]>::to_vec);
+        let task = tasks.spawn(async move {
+            match requested {
+                Some(requested) => collector.collect_fresh_into(&registry, &requested).await,
+                None => {
+                    collector.refreshed_registry().await;
+                }
+            }
+        });
+        task_indices.insert(task.id(), index);
+    }
+
+    while let Some(joined) = tasks.join_next_with_id().await {
+        if let Err(e) = joined {
+            let index = task_indices[&e.id()];
+            warn!(
+                "Collection of PBS server {} failed: {}",
+                collectors[index]
+                    .server_label
+                    .as_deref()
+                    .unwrap_or(DEFAULT_TARGET_NAME),
+                e
+            );
+            registries[index].pbs_up.set(0.0);
+        }
+    }
+    registry::encode_merged(registries.iter())
+}
+
+/// Usage reports of several PBS servers, concatenated in server order.
+///
+/// Servers are refreshed concurrently, and in static multi-target mode every
+/// row carries its server's `pbs_server` label.
+///
+/// # Errors
+///
+/// Returns an error if a report task panicked or any server has no report.
+pub async fn usage_report_all(collectors: &[MetricsCollector]) -> Result<Vec<UsageRow>> {
+    let mut tasks = JoinSet::new();
+    for (index, collector) in collectors.iter().cloned().enumerate() {
+        tasks.spawn(async move { (index, collector.usage_report().await) });
+    }
+
+    let mut reports = Vec::with_capacity(collectors.len());
+    while let Some(joined) = tasks.join_next().await {
+        let (index, report) = joined.map_err(|e| PbsError::Collection(e.to_string()))?;
+        reports.push((index, report?));
+    }
+    reports.sort_by_key(|(index, _)| *index);
+    Ok(reports.into_iter().flat_map(|(_, rows)| rows).collect())
 }
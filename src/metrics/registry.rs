@@ -1,7 +1,14 @@
 //! Metric registry and builder pattern for reducing repetitive registration code.
 
+use crate::client::ApiMetrics;
 use crate::error::{PbsError, Result};
+use prometheus::proto::MetricFamily;
 use prometheus::{Encoder, Gauge, GaugeVec, Opts, Registry, TextEncoder};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, PoisonError, RwLock, RwLockWriteGuard};
+
+/// Label identifying the PBS server in static multi-target mode.
+const SERVER_LABEL: &str = "pbs_server";
 
 /// Helper struct for building metrics with less boilerplate.
 struct MetricBuilder<'a> {
@@ -34,43 +41,118 @@ impl<'a> MetricBuilder<'a> {
     }
 }
 
+/// Last and next run gauges shared by every PBS job type, labelled by job ID
+/// and datastore.
+#[derive(Clone)]
+pub(crate) struct JobScheduleMetrics {
+    pub(crate) last_run_timestamp_seconds: GaugeVec,
+    pub(crate) status: GaugeVec,
+    pub(crate) duration_seconds: GaugeVec,
+    pub(crate) next_run_timestamp_seconds: GaugeVec,
+}
+
+impl JobScheduleMetrics {
+    /// Register `<prefix>_last_run_timestamp_seconds`, `<prefix>_status`,
+    /// `<prefix>_duration_seconds` and `<prefix>_next_run_timestamp_seconds`.
+    fn new(builder: &MetricBuilder<'_>, prefix: &str, job: &str) -> Result<Self> {
+        let labels = ["job_id", "datastore"];
+        Ok(Self {
+            last_run_timestamp_seconds: builder.gauge_vec(
+                &format!("{}_last_run_timestamp_seconds", prefix),
+                &format!("Last {} run completion timestamp", job),
+                &labels,
+            )?,
+            status: builder.gauge_vec(
+                &format!("{}_status", prefix),
+                &format!("Last {} run status (1=OK, 0=ERROR)", job),
+                &labels,
+            )?,
+            duration_seconds: builder.gauge_vec(
+                &format!("{}_duration_seconds", prefix),
+                &format!("Last {} run duration in seconds", job),
+                &labels,
+            )?,
+            next_run_timestamp_seconds: builder.gauge_vec(
+                &format!("{}_next_run_timestamp_seconds", prefix),
+                &format!("Next scheduled {} run timestamp", job),
+                &labels,
+            )?,
+        })
+    }
+
+    pub(crate) fn reset(&self) {
+        self.last_run_timestamp_seconds.reset();
+        self.status.reset();
+        self.duration_seconds.reset();
+        self.next_run_timestamp_seconds.reset();
+    }
+}
+
 /// Registry holding all metric instances.
 #[derive(Clone)]
 pub struct MetricRegistry {
     registry: Registry,
+    /// Held for writing while metrics are reset and repopulated, and for
+    /// reading while they are gathered, so scrapes never see a half-reset registry.
+    update_lock: Arc<RwLock<()>>,
 
     // Exporter metrics
     pub(crate) pbs_up: Gauge,
     pub(crate) exporter_scrape_duration_seconds: Gauge,
     pub(crate) exporter_memory_usage_bytes: Gauge,
-    #[allow(dead_code)] // Reserved for future API call tracking
-    pub(crate) exporter_api_calls_total: GaugeVec,
+    pub(crate) exporter_last_collection_timestamp_seconds: Gauge,
+    pub(crate) exporter_last_collection_age_seconds: Gauge,
+    pub(crate) exporter_last_collection_attempt_timestamp_seconds: Gauge,
+    pub(crate) collector_success: GaugeVec,
+    pub(crate) collector_duration_seconds: GaugeVec,
 
     // Host metrics
-    pub(crate) host_cpu_usage: Gauge,
-    pub(crate) host_io_wait: Gauge,
-    pub(crate) host_load1: Gauge,
-    pub(crate) host_load5: Gauge,
-    pub(crate) host_load15: Gauge,
-    pub(crate) host_memory_used_bytes: Gauge,
-    pub(crate) host_memory_total_bytes: Gauge,
-    pub(crate) host_memory_free_bytes: Gauge,
-    pub(crate) host_swap_used_bytes: Gauge,
-    pub(crate) host_swap_total_bytes: Gauge,
-    pub(crate) host_swap_free_bytes: Gauge,
-    pub(crate) host_rootfs_used_bytes: Gauge,
-    pub(crate) host_rootfs_total_bytes: Gauge,
-    pub(crate) host_rootfs_avail_bytes: Gauge,
-    pub(crate) host_uptime_seconds: Gauge,
+    pub(crate) host_cpu_usage: GaugeVec,
+    pub(crate) host_io_wait: GaugeVec,
+    pub(crate) host_load1: GaugeVec,
+    pub(crate) host_load5: GaugeVec,
+    pub(crate) host_load15: GaugeVec,
+    pub(crate) host_memory_used_bytes: GaugeVec,
+    pub(crate) host_memory_total_bytes: GaugeVec,
+    pub(crate) host_memory_free_bytes: GaugeVec,
+    pub(crate) host_swap_used_bytes: GaugeVec,
+    pub(crate) host_swap_total_bytes: GaugeVec,
+    pub(crate) host_swap_free_bytes: GaugeVec,
+    pub(crate) host_rootfs_used_bytes: GaugeVec,
+    pub(crate) host_rootfs_total_bytes: GaugeVec,
+    pub(crate) host_rootfs_avail_bytes: GaugeVec,
+    pub(crate) host_uptime_seconds: GaugeVec,
 
     // Datastore metrics
     pub(crate) datastore_total_bytes: GaugeVec,
     pub(crate) datastore_used_bytes: GaugeVec,
     pub(crate) datastore_available_bytes: GaugeVec,
+    pub(crate) datastore_estimated_full_timestamp_seconds: GaugeVec,
+    pub(crate) datastore_usage_history_ratio: GaugeVec,
+    pub(crate) datastore_growth_bytes_per_day: GaugeVec,
+    pub(crate) datastore_predicted_full_timestamp_seconds: GaugeVec,
+    pub(crate) datastore_forecast_r_squared: GaugeVec,
+    pub(crate) datastore_groups: GaugeVec,
+    pub(crate) datastore_snapshots: GaugeVec,
 
     // Backup metrics
     pub(crate) snapshot_count: GaugeVec,
     pub(crate) snapshot_last_timestamp_seconds: GaugeVec,
+    /// Whether the backup group metrics above carry an `owner` label.
+    pub(crate) group_owner_label: bool,
+    pub(crate) owner_snapshot_count: GaugeVec,
+    pub(crate) owner_bytes: GaugeVec,
+    pub(crate) backup_groups_filtered: GaugeVec,
+    pub(crate) backup_group_age_seconds: GaugeVec,
+    pub(crate) backup_group_max_age_seconds: GaugeVec,
+    pub(crate) backup_group_stale: GaugeVec,
+    pub(crate) retention_expected_buckets: GaugeVec,
+    pub(crate) retention_missing_buckets: GaugeVec,
+    pub(crate) backup_group_encrypted: GaugeVec,
+    pub(crate) backup_group_archive_size_bytes: GaugeVec,
+    pub(crate) usage_logical_bytes: GaugeVec,
+    pub(crate) usage_latest_snapshot_bytes: GaugeVec,
+    pub(crate) usage_growth_bytes: GaugeVec,
 
     // Individual snapshot metrics
     pub(crate) snapshot_info: GaugeVec,
@@ -91,10 +173,49 @@ pub struct MetricRegistry {
     pub(crate) gc_removed_bytes: GaugeVec,
     pub(crate) gc_pending_bytes: GaugeVec,
     pub(crate) gc_status: GaugeVec,
+    pub(crate) gc_index_data_bytes: GaugeVec,
+    pub(crate) gc_disk_bytes: GaugeVec,
+    pub(crate) gc_disk_chunks: GaugeVec,
+    pub(crate) gc_index_file_count: GaugeVec,
+    pub(crate) gc_removed_chunks: GaugeVec,
+    pub(crate) datastore_deduplication_ratio: GaugeVec,
+
+    // Sync job metrics
+    pub(crate) sync_job_info: GaugeVec,
+    pub(crate) sync_jobs: JobScheduleMetrics,
+
+    // Verify job metrics
+    pub(crate) verify_job_info: GaugeVec,
+    pub(crate) verify_job_outdated_after_seconds: GaugeVec,
+    pub(crate) verify_job_ignore_verified: GaugeVec,
+    pub(crate) verify_jobs: JobScheduleMetrics,
+
+    // Prune job metrics
+    pub(crate) prune_job_info: GaugeVec,
+    pub(crate) prune_job_keep: GaugeVec,
+    pub(crate) prune_jobs: JobScheduleMetrics,
 
     // Tape metrics
     pub(crate) tape_drive_info: GaugeVec,
     pub(crate) tape_drive_available: Gauge,
+    pub(crate) tape_drive_busy: GaugeVec,
+    pub(crate) tape_drive_activity: GaugeVec,
+    pub(crate) tape_drive_media_loaded: GaugeVec,
+    pub(crate) tape_drive_compression: GaugeVec,
+    pub(crate) tape_drive_write_protected: GaugeVec,
+    pub(crate) tape_drive_alert: GaugeVec,
+    pub(crate) tape_changer_info: GaugeVec,
+    pub(crate) tape_changer_slots: GaugeVec,
+    pub(crate) tape_changer_slots_occupied: GaugeVec,
+    pub(crate) tape_backup_job_info: GaugeVec,
+    pub(crate) tape_backup_jobs: JobScheduleMetrics,
+    pub(crate) tape_media_pool_info: GaugeVec,
+    pub(crate) tape_media_pool_encrypted: GaugeVec,
+    pub(crate) tape_media_pool_media: GaugeVec,
+    pub(crate) tape_media_pool_bytes_used: GaugeVec,
+    pub(crate) tape_media_info: GaugeVec,
+    pub(crate) tape_media_expired: GaugeVec,
+    pub(crate) tape_media_bytes_used: GaugeVec,
 
     // Version info
     pub(crate) pbs_version: GaugeVec,
@@ -113,23 +234,56 @@ impl MetricRegistry {
     ///
     /// # Metrics Registered
     ///
-    /// - Exporter metrics (pbs_up, scrape_duration, memory_usage, api_calls)
+    /// - Exporter metrics (pbs_up, scrape_duration, memory_usage, last_collection)
+    /// - Per-collector success and duration
     /// - Host/node metrics (CPU, memory, swap, disk, load, uptime)
-    /// - Datastore metrics (total, used, available bytes)
+    /// - Datastore metrics (total, used, available bytes, estimated full date,
+    ///   usage history, fill-rate forecast, group/snapshot counts per backup type)
     /// - Snapshot metrics (count, timestamp, size, verification, protection)
     /// - Task metrics (total, duration, last_run, running)
     /// - Garbage collection metrics (timestamp, duration, removed/pending bytes, status)
-    /// - Tape drive metrics (info, available count)
+    /// - Sync, verify and prune job metrics (info, last run, status, duration,
+    ///   next run, prune retention settings)
+    /// - Tape metrics (drives and their live status, changer slots, backup jobs,
+    ///   media pools, media inventory)
     /// - Version information
     pub fn new() -> Result<Self> {
-        let registry = Registry::new();
+        Self::with_options(None, false)
+    }
+
+    /// Create a metric registry, optionally labelled with `pbs_server` and with
+    /// an `owner` label on the backup group metrics.
+    pub(crate) fn with_options(server: Option<&str>, group_owner_label: bool) -> Result<Self> {
+        let registry = match server {
+            Some(server) => {
+                let labels = HashMap::from([(SERVER_LABEL.to_string(), server.to_string())]);
+                Registry::new_custom(None, Some(labels))
+                    .map_err(|e| PbsError::Metrics(e.to_string()))?
+            }
+            None => Registry::new(),
+        };
+        Self::build(registry, group_owner_label)
+    }
+
+    /// Register all PBS exporter metrics in the given Prometheus registry.
+    fn build(registry: Registry, group_owner_label: bool) -> Result<Self> {
         let builder = MetricBuilder::new(&registry);
+        let mut group_labels = vec![
+            "datastore",
+            "namespace",
+            "backup_type",
+            "backup_id",
+            "comment",
+        ];
+        if group_owner_label {
+            group_labels.push("owner");
+        }
 
         Ok(Self {
             // Exporter metrics
             pbs_up: builder.gauge(
                 "pbs_up",
-                "Whether the last scrape of PBS was successful (1 = success, 0 = failure)",
+                "Whether PBS answered the last collection (1 = reachable, 0 = down)",
             )?,
             exporter_scrape_duration_seconds: builder.gauge(
                 "pbs_exporter_scrape_duration_seconds",
@@ -139,56 +293,97 @@ impl MetricRegistry {
                 "pbs_exporter_memory_usage_bytes",
                 "Current memory usage of the exporter in bytes",
             )?,
-            exporter_api_calls_total: builder.gauge_vec(
-                "pbs_exporter_api_calls_total",
-                "Total number of API calls made to PBS",
-                &["endpoint", "status"],
+            exporter_last_collection_timestamp_seconds: builder.gauge(
+                "pbs_exporter_last_collection_timestamp_seconds",
+                "Unix timestamp of the last successful collection from PBS",
+            )?,
+            exporter_last_collection_age_seconds: builder.gauge(
+                "pbs_exporter_last_collection_age_seconds",
+                "Age of the served metrics in seconds (time since the last successful collection)",
+            )?,
+            exporter_last_collection_attempt_timestamp_seconds: builder.gauge(
+                "pbs_exporter_last_collection_attempt_timestamp_seconds",
+                "Unix timestamp of the last collection attempt, successful or not",
+            )?,
+            collector_success: builder.gauge_vec(
+                "pbs_collector_success",
+                "Whether the collector succeeded in the last collection (1 = success, 0 = failure)",
+                &["collector", "datastore"],
+            )?,
+            collector_duration_seconds: builder.gauge_vec(
+                "pbs_collector_duration_seconds",
+                "Time spent in the collector's PBS API calls during the last collection",
+                &["collector"],
             )?,
 
             // Host metrics
-            host_cpu_usage: builder.gauge(
+            host_cpu_usage: builder.gauge_vec(
                 "pbs_host_cpu_usage",
                 "CPU usage of the PBS host (fraction of 1.0)",
+                &["node"],
             )?,
-            host_io_wait: builder.gauge(
+            host_io_wait: builder.gauge_vec(
                 "pbs_host_io_wait",
                 "CPU I/O wait proportion (fraction of 1.0)",
+                &["node"],
             )?,
-            host_load1: builder.gauge("pbs_host_load1", "1-minute load average")?,
-            host_load5: builder.gauge("pbs_host_load5", "5-minute load average")?,
-            host_load15: builder.gauge("pbs_host_load15", "15-minute load average")?,
-            host_memory_used_bytes: builder.gauge(
+            host_load1: builder.gauge_vec("pbs_host_load1", "1-minute load average", &["node"])?,
+            host_load5: builder.gauge_vec("pbs_host_load5", "5-minute load average", &["node"])?,
+            host_load15: builder.gauge_vec(
+                "pbs_host_load15",
+                "15-minute load average",
+                &["node"],
+            )?,
+            host_memory_used_bytes: builder.gauge_vec(
                 "pbs_host_memory_used_bytes",
                 "Used RAM on PBS host in bytes",
+                &["node"],
             )?,
-            host_memory_total_bytes: builder.gauge(
+            host_memory_total_bytes: builder.gauge_vec(
                 "pbs_host_memory_total_bytes",
                 "Total RAM on PBS host in bytes",
+                &["node"],
             )?,
-            host_memory_free_bytes: builder.gauge(
+            host_memory_free_bytes: builder.gauge_vec(
                 "pbs_host_memory_free_bytes",
                 "Free RAM on PBS host in bytes",
+                &["node"],
+            )?,
+            host_swap_used_bytes: builder.gauge_vec(
+                "pbs_host_swap_used_bytes",
+                "Used swap space in bytes",
+                &["node"],
             )?,
-            host_swap_used_bytes: builder
-                .gauge("pbs_host_swap_used_bytes", "Used swap space in bytes")?,
-            host_swap_total_bytes: builder
-                .gauge("pbs_host_swap_total_bytes", "Total swap space in bytes")?,
-            host_swap_free_bytes: builder
-                .gauge("pbs_host_swap_free_bytes", "Free swap space in bytes")?,
-            host_rootfs_used_bytes: builder.gauge(
+            host_swap_total_bytes: builder.gauge_vec(
+                "pbs_host_swap_total_bytes",
+                "Total swap space in bytes",
+                &["node"],
+            )?,
+            host_swap_free_bytes: builder.gauge_vec(
+                "pbs_host_swap_free_bytes",
+                "Free swap space in bytes",
+                &["node"],
+            )?,
+            host_rootfs_used_bytes: builder.gauge_vec(
                 "pbs_host_rootfs_used_bytes",
                 "Used bytes on root filesystem",
+                &["node"],
             )?,
-            host_rootfs_total_bytes: builder.gauge(
+            host_rootfs_total_bytes: builder.gauge_vec(
                 "pbs_host_rootfs_total_bytes",
                 "Total bytes on root filesystem",
+                &["node"],
             )?,
-            host_rootfs_avail_bytes: builder.gauge(
+            host_rootfs_avail_bytes: builder.gauge_vec(
                 "pbs_host_rootfs_avail_bytes",
                 "Available bytes on root filesystem",
+                &["node"],
+            )?,
+            host_uptime_seconds: builder.gauge_vec(
+                "pbs_host_uptime_seconds",
+                "Uptime of PBS host in seconds",
+                &["node"],
             )?,
-            host_uptime_seconds: builder
-                .gauge("pbs_host_uptime_seconds", "Uptime of PBS host in seconds")?,
 
             // Datastore metrics
             datastore_total_bytes: builder.gauge_vec(
@@ -206,17 +401,123 @@ impl MetricRegistry {
                 "Available bytes in datastore",
                 &["datastore"],
             )?,
+            datastore_estimated_full_timestamp_seconds: builder.gauge_vec(
+                "pbs_datastore_estimated_full_timestamp_seconds",
+                "PBS estimate of when the datastore will be full (Unix timestamp)",
+                &["datastore"],
+            )?,
+            datastore_usage_history_ratio: builder.gauge_vec(
+                "pbs_datastore_usage_history_ratio",
+                "Datastore usage (fraction of total) from PBS usage history, N days ago",
+                &["datastore", "days_ago"],
+            )?,
+            datastore_growth_bytes_per_day: builder.gauge_vec(
+                "pbs_datastore_growth_bytes_per_day",
+                "Datastore growth rate fitted over the exporter's forecast window",
+                &["datastore"],
+            )?,
+            datastore_predicted_full_timestamp_seconds: builder.gauge_vec(
+                "pbs_datastore_predicted_full_timestamp_seconds",
+                "Exporter forecast of when the datastore will be full (Unix timestamp)",
+                &["datastore"],
+            )?,
+            datastore_forecast_r_squared: builder.gauge_vec(
+                "pbs_datastore_forecast_r_squared",
+                "Goodness of fit of the datastore growth forecast (0-1, 1 = perfect linear growth)",
+                &["datastore"],
+            )?,
+            datastore_groups: builder.gauge_vec(
+                "pbs_datastore_groups",
+                "Number of backup groups in datastore by backup type",
+                &["datastore", "backup_type"],
+            )?,
+            datastore_snapshots: builder.gauge_vec(
+                "pbs_datastore_snapshots",
+                "Number of snapshots in datastore by backup type",
+                &["datastore", "backup_type"],
+            )?,
 
             // Backup metrics
             snapshot_count: builder.gauge_vec(
                 "pbs_snapshot_count",
                 "Number of backup snapshots",
-                &["datastore", "backup_type", "backup_id", "comment"],
+                &group_labels,
             )?,
             snapshot_last_timestamp_seconds: builder.gauge_vec(
                 "pbs_snapshot_last_timestamp_seconds",
                 "Unix timestamp of last backup",
-                &["datastore", "backup_type", "backup_id", "comment"],
+                &group_labels,
+            )?,
+            group_owner_label,
+            owner_snapshot_count: builder.gauge_vec(
+                "pbs_owner_snapshot_count",
+                "Number of snapshots owned by each backup owner",
+                &["datastore", "owner"],
+            )?,
+            owner_bytes: builder.gauge_vec(
+                "pbs_owner_bytes",
+                "Total size of the snapshots owned by each backup owner",
+                &["datastore", "owner"],
+            )?,
+
+            backup_groups_filtered: builder.gauge_vec(
+                "pbs_backup_groups_filtered",
+                "Number of backup groups left out of snapshot and group metrics by the configured filters",
+                &["datastore"],
+            )?,
+
+            backup_group_age_seconds: builder.gauge_vec(
+                "pbs_backup_group_age_seconds",
+                "Seconds since the latest backup of a group with a freshness SLO",
+                &["datastore", "namespace", "backup_type", "backup_id"],
+            )?,
+            backup_group_max_age_seconds: builder.gauge_vec(
+                "pbs_backup_group_max_age_seconds",
+                "Maximum backup age allowed by the group's freshness SLO",
+                &["datastore", "namespace", "backup_type", "backup_id"],
+            )?,
+            backup_group_stale: builder.gauge_vec(
+                "pbs_backup_group_stale",
+                "Whether the latest backup is older than the group's freshness SLO (1=stale, 0=fresh)",
+                &["datastore", "namespace", "backup_type", "backup_id"],
+            )?,
+
+            retention_expected_buckets: builder.gauge_vec(
+                "pbs_retention_expected_buckets",
+                "Number of consecutive periods expected to contain a snapshot by the group's retention rule",
+                &["datastore", "namespace", "backup_type", "backup_id", "bucket"],
+            )?,
+            retention_missing_buckets: builder.gauge_vec(
+                "pbs_retention_missing_buckets",
+                "Number of expected retention periods without any snapshot",
+                &["datastore", "namespace", "backup_type", "backup_id", "bucket"],
+            )?,
+
+            backup_group_encrypted: builder.gauge_vec(
+                "pbs_backup_group_encrypted",
+                "Whether every archive of the group's latest snapshot is encrypted (crypt_mode = encrypt, sign-only, none or mixed)",
+                &["datastore", "namespace", "backup_type", "backup_id", "crypt_mode"],
+            )?,
+            backup_group_archive_size_bytes: builder.gauge_vec(
+                "pbs_backup_group_archive_size_bytes",
+                "Size of each archive in the group's latest snapshot",
+                &["datastore", "namespace", "backup_type", "backup_id", "archive"],
+            )?,
+
+            usage_logical_bytes: builder.gauge_vec(
+                "pbs_usage_logical_bytes",
+                "Sum of the sizes of all snapshots in a backup group",
+                &["datastore", "namespace", "owner", "backup_type", "backup_id"],
+            )?,
+            usage_latest_snapshot_bytes: builder.gauge_vec(
+                "pbs_usage_latest_snapshot_bytes",
+                "Size of the latest snapshot in a backup group",
+                &["datastore", "namespace", "owner", "backup_type", "backup_id"],
+            )?,
+            usage_growth_bytes: builder.gauge_vec(
+                "pbs_usage_growth_bytes",
+                "Change of the latest snapshot size over the usage growth window",
+                &["datastore", "namespace", "owner", "backup_type", "backup_id"],
             )?,
 
             // Individual snapshot metrics
@@ -225,6 +526,7 @@ impl MetricRegistry {
                 "Individual snapshot information with timestamp as value",
                 &[
                     "datastore",
+                    "namespace",
                     "backup_type",
                     "backup_id",
                     "comment",
@@ -236,6 +538,7 @@ impl MetricRegistry {
                 "Size of individual snapshot in bytes",
                 &[
                     "datastore",
+                    "namespace",
                     "backup_type",
                     "backup_id",
                     "comment",
@@ -248,6 +551,7 @@ impl MetricRegistry {
                 "Timestamp of last verification in seconds",
                 &[
                     "datastore",
+                    "namespace",
                     "backup_type",
                     "backup_id",
                     "comment",
@@ -259,6 +563,7 @@ impl MetricRegistry {
                 "Snapshot verification status (1=ok, 0=failed/unknown)",
                 &[
                     "datastore",
+                    "namespace",
                     "backup_type",
                     "backup_id",
                     "comment",
@@ -270,6 +575,7 @@ impl MetricRegistry {
                 "Snapshot protection status (1=protected, 0=not protected)",
                 &[
                     "datastore",
+                    "namespace",
                     "backup_type",
                     "backup_id",
                     "comment",
@@ -281,22 +587,22 @@ impl MetricRegistry {
             task_total: builder.gauge_vec(
                 "pbs_task_total",
                 "Total number of tasks (by worker type/status)",
-                &["worker_type", "status", "comment"],
+                &["worker_type", "status", "comment", "node"],
             )?,
             task_duration_seconds: builder.gauge_vec(
                 "pbs_task_duration_seconds",
                 "Task duration in seconds",
-                &["worker_type", "status", "worker_id", "comment"],
+                &["worker_type", "status", "worker_id", "comment", "node"],
             )?,
             task_last_run_timestamp: builder.gauge_vec(
                 "pbs_task_last_run_timestamp",
                 "Last run timestamp for task type",
-                &["worker_type"],
+                &["worker_type", "node"],
             )?,
             task_running: builder.gauge_vec(
                 "pbs_task_running",
                 "Currently running tasks",
-                &["worker_type", "comment"],
+                &["worker_type", "comment", "node"],
             )?,
 
             // GC metrics
@@ -325,6 +631,82 @@ impl MetricRegistry {
                 "Last GC status (1=OK, 0=ERROR)",
                 &["datastore"],
             )?,
+            gc_index_data_bytes: builder.gauge_vec(
+                "pbs_gc_index_data_bytes",
+                "Logical bytes referenced by all index files at the last GC",
+                &["datastore"],
+            )?,
+            gc_disk_bytes: builder.gauge_vec(
+                "pbs_gc_disk_bytes",
+                "Bytes used by chunks on disk at the last GC",
+                &["datastore"],
+            )?,
+            gc_disk_chunks: builder.gauge_vec(
+                "pbs_gc_disk_chunks",
+                "Chunks on disk at the last GC",
+                &["datastore"],
+            )?,
+            gc_index_file_count: builder.gauge_vec(
+                "pbs_gc_index_file_count",
+                "Index files processed by the last GC",
+                &["datastore"],
+            )?,
+            gc_removed_chunks: builder.gauge_vec(
+                "pbs_gc_removed_chunks",
+                "Chunks removed in last GC",
+                &["datastore"],
+            )?,
+            datastore_deduplication_ratio: builder.gauge_vec(
+                "pbs_datastore_deduplication_ratio",
+                "Logical index bytes per physical byte on disk at the last GC",
+                &["datastore"],
+            )?,
+
+            // Sync job metrics
+            sync_job_info: builder.gauge_vec(
+                "pbs_sync_job_info",
+                "Sync job source and target",
+                &[
+                    "job_id",
+                    "datastore",
+                    "namespace",
+                    "remote",
+                    "remote_store",
+                    "remote_namespace",
+                ],
+            )?,
+            sync_jobs: JobScheduleMetrics::new(&builder, "pbs_sync_job", "sync job")?,
+
+            // Verify job metrics
+            verify_job_info: builder.gauge_vec(
+                "pbs_verify_job_info",
+                "Verify job namespace and schedule",
+                &["job_id", "datastore", "namespace", "schedule"],
+            )?,
+            verify_job_outdated_after_seconds: builder.gauge_vec(
+                "pbs_verify_job_outdated_after_seconds",
+                "Age after which a verified snapshot is verified again",
+                &["job_id", "datastore"],
+            )?,
+            verify_job_ignore_verified: builder.gauge_vec(
+                "pbs_verify_job_ignore_verified",
+                "Whether the verify job skips already verified snapshots (1=yes, 0=no)",
+                &["job_id", "datastore"],
+            )?,
+            verify_jobs: JobScheduleMetrics::new(&builder, "pbs_verify_job", "verify job")?,
+
+            // Prune job metrics
+            prune_job_info: builder.gauge_vec(
+                "pbs_prune_job_info",
+                "Prune job namespace and schedule",
+                &["job_id", "datastore", "namespace", "schedule"],
+            )?,
+            prune_job_keep: builder.gauge_vec(
+                "pbs_prune_job_keep",
+                "Number of backups kept per retention option (keep-last, keep-daily, ...)",
+                &["job_id", "datastore", "namespace", "keep"],
+            )?,
+            prune_jobs: JobScheduleMetrics::new(&builder, "pbs_prune_job", "prune job")?,
 
             // Tape metrics
             tape_drive_info: builder.gauge_vec(
@@ -336,6 +718,103 @@ impl MetricRegistry {
                 "pbs_tape_drive_available",
                 "Number of available tape drives",
             )?,
+            tape_drive_busy: builder.gauge_vec(
+                "pbs_tape_drive_busy",
+                "Whether a task holds the tape drive (1=busy, 0=idle)",
+                &["name"],
+            )?,
+            tape_drive_activity: builder.gauge_vec(
+                "pbs_tape_drive_activity",
+                "Current tape drive activity (always 1, activity in label)",
+                &["name", "activity"],
+            )?,
+            tape_drive_media_loaded: builder.gauge_vec(
+                "pbs_tape_drive_media_loaded",
+                "Whether the changer reports a medium in the drive (1=loaded, 0=empty)",
+                &["name", "label_text"],
+            )?,
+            tape_drive_compression: builder.gauge_vec(
+                "pbs_tape_drive_compression",
+                "Whether hardware compression is enabled (1=yes, 0=no)",
+                &["name"],
+            )?,
+            tape_drive_write_protected: builder.gauge_vec(
+                "pbs_tape_drive_write_protected",
+                "Whether the loaded medium is write protected (1=yes, 0=no)",
+                &["name"],
+            )?,
+            tape_drive_alert: builder.gauge_vec(
+                "pbs_tape_drive_alert",
+                "Active TapeAlert flag (always 1, flag in label)",
+                &["name", "flag"],
+            )?,
+            tape_changer_info: builder.gauge_vec(
+                "pbs_tape_changer_info",
+                "Tape changer information",
+                &["name", "vendor", "model", "serial"],
+            )?,
+            tape_changer_slots: builder.gauge_vec(
+                "pbs_tape_changer_slots",
+                "Number of changer elements per kind (drive, slot, import-export)",
+                &["name", "kind"],
+            )?,
+            tape_changer_slots_occupied: builder.gauge_vec(
+                "pbs_tape_changer_slots_occupied",
+                "Number of changer elements holding a medium per kind",
+                &["name", "kind"],
+            )?,
+            tape_backup_job_info: builder.gauge_vec(
+                "pbs_tape_backup_job_info",
+                "Tape backup job source, target and schedule",
+                &[
+                    "job_id",
+                    "datastore",
+                    "namespace",
+                    "pool",
+                    "drive",
+                    "schedule",
+                ],
+            )?,
+            tape_backup_jobs: JobScheduleMetrics::new(
+                &builder,
+                "pbs_tape_backup_job",
+                "tape backup job",
+            )?,
+            tape_media_pool_info: builder.gauge_vec(
+                "pbs_tape_media_pool_info",
+                "Media pool allocation and retention policy",
+                &["pool", "allocation", "retention"],
+            )?,
+            tape_media_pool_encrypted: builder.gauge_vec(
+                "pbs_tape_media_pool_encrypted",
+                "Whether the media pool encrypts its media (1=yes, 0=no)",
+                &["pool"],
+            )?,
+            tape_media_pool_media: builder.gauge_vec(
+                "pbs_tape_media_pool_media",
+                "Number of media in the pool per media status",
+                &["pool", "status"],
+            )?,
+            tape_media_pool_bytes_used: builder.gauge_vec(
+                "pbs_tape_media_pool_bytes_used",
+                "Bytes written to the pool's media",
+                &["pool"],
+            )?,
+            tape_media_info: builder.gauge_vec(
+                "pbs_tape_media_info",
+                "Tape medium pool, media set, location and status",
+                &["label_text", "pool", "media_set", "location", "status"],
+            )?,
+            tape_media_expired: builder.gauge_vec(
+                "pbs_tape_media_expired",
+                "Whether the medium's media set has passed its retention period (1=yes, 0=no)",
+                &["label_text", "pool"],
+            )?,
+            tape_media_bytes_used: builder.gauge_vec(
+                "pbs_tape_media_bytes_used",
+                "Bytes written to the medium",
+                &["label_text", "pool"],
+            )?,
 
             // Version info
             pbs_version: builder.gauge_vec(
@@ -345,38 +824,106 @@ impl MetricRegistry {
             )?,
 
             registry,
+            update_lock: Arc::new(RwLock::new(())),
         })
     }
 
+    /// Register a client's API call counters and latency histograms.
+    ///
+    /// These instruments are owned by the client rather than the registry, so
+    /// they are never reset between collections.
+    pub(crate) fn register_api_metrics(&self, api_metrics: &ApiMetrics) -> Result<()> {
+        self.registry
+            .register(Box::new(api_metrics.calls_total.clone()))
+            .map_err(|e| PbsError::Metrics(e.to_string()))?;
+        self.registry
+            .register(Box::new(api_metrics.request_duration_seconds.clone()))
+            .map_err(|e| PbsError::Metrics(e.to_string()))?;
+        self.registry
+            .register(Box::new(api_metrics.retries_total.clone()))
+            .map_err(|e| PbsError::Metrics(e.to_string()))?;
+        self.registry
+            .register(Box::new(api_metrics.circuit_breaker_state.clone()))
+            .map_err(|e| PbsError::Metrics(e.to_string()))?;
+        Ok(())
+    }
+
+    /// Block `encode()` until the returned guard is dropped.
+    ///
+    /// Used by the collector to make the reset/repopulate cycle atomic with
+    /// respect to scrapes.
+    pub(crate) fn update_guard(&self) -> RwLockWriteGuard<'_, ()> {
+        self.update_lock
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
     /// Encode metrics in Prometheus text format.
     ///
     /// Serializes all metrics in the registry to Prometheus exposition format.
-    /// Uses a thread-local buffer to minimize allocations on repeated calls.
     ///
     /// # Returns
     ///
     /// Returns a Result containing the encoded metrics as a UTF-8 String,
     /// or an error if encoding fails.
     pub fn encode(&self) -> Result<String> {
-        let encoder = TextEncoder::new();
-        let metric_families = self.registry.gather();
+        encode_families(&self.gather())
+    }
 
-        // Keep a thread-local scratch buffer while encoding the current scrape.
-        thread_local! {
-            static BUFFER: std::cell::RefCell<Vec<u8>> = std::cell::RefCell::new(Vec::with_capacity(8192));
-        }
+    /// Gather all metric families while no update is in progress.
+    pub(crate) fn gather(&self) -> Vec<MetricFamily> {
+        let _update = self
+            .update_lock
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.registry.gather()
+    }
+}
 
-        BUFFER.with(|buf| {
-            let mut buffer = buf.borrow_mut();
-            buffer.clear();
+/// Encode the metrics of several registries as one Prometheus response.
+///
+/// Families with the same name are merged so each metric is emitted once with
+/// a single HELP/TYPE header; the registries must carry distinguishing
+/// `pbs_server` labels for the merged series to stay unique.
+pub(crate) fn encode_merged<'a>(
+    registries: impl IntoIterator<Item = &'a MetricRegistry>,
+) -> Result<String> {
+    let mut merged: BTreeMap<String, MetricFamily> = BTreeMap::new();
+    for registry in registries {
+        for mut family in registry.gather() {
+            match merged.get_mut(family.name()) {
+                Some(existing) => existing.mut_metric().extend(family.take_metric()),
+                None => {
+                    merged.insert(family.name().to_string(), family);
+                }
+            }
+        }
+    }
+    let families: Vec<MetricFamily> = merged.into_values().collect();
+    encode_families(&families)
+}
 
-            encoder
-                .encode(&metric_families, &mut *buffer)
-                .map_err(|e| PbsError::Metrics(e.to_string()))?;
+/// Encode gathered metric families in Prometheus text format.
+///
+/// Uses a thread-local buffer to minimize allocations on repeated calls.
+fn encode_families(metric_families: &[MetricFamily]) -> Result<String> {
+    let encoder = TextEncoder::new();
 
-            // Move bytes out instead of cloning to save memory
-            let bytes = std::mem::take(&mut *buffer);
-            String::from_utf8(bytes).map_err(|e| PbsError::Metrics(e.to_string()))
-        })
+    // Keep a thread-local scratch buffer while encoding the current scrape.
+    thread_local! {
+        static BUFFER: std::cell::RefCell<Vec<u8>> = std::cell::RefCell::new(Vec::with_capacity(8192));
     }
+
+    BUFFER.with(|buf| {
+        let mut buffer = buf.borrow_mut();
+        buffer.clear();
+
+        encoder
+            .encode(metric_families, &mut *buffer)
+            .map_err(|e| PbsError::Metrics(e.to_string()))?;
+
+        // Move bytes out instead of cloning to save memory
+        let bytes = std::mem::take(&mut *buffer);
+        String::from_utf8(bytes).map_err(|e| PbsError::Metrics(e.to_string()))
+    })
 }
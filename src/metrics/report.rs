@@ -0,0 +1,230 @@
+//! Logical storage usage per backup group, served as a CSV or JSON report.
+
+use super::BackupFilter;
+use crate::client::Snapshot;
+use serde::Serialize;
+use std::collections::HashMap;
+
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// Logical storage usage of one backup group.
+///
+/// Sizes are the logical snapshot sizes PBS reports, before deduplication and
+/// compression, so they add up to what each tenant backed up rather than what
+/// the datastore stores.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UsageRow {
+    /// `pbs_server` label in static multi-target mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Datastore name
+    pub datastore: String,
+    /// Namespace (empty for the root namespace)
+    pub namespace: String,
+    /// Owner of the group's latest snapshot (empty if PBS did not report one)
+    pub owner: String,
+    /// Backup type (vm, ct, host)
+    pub backup_type: String,
+    /// Backup ID
+    pub backup_id: String,
+    /// Number of snapshots in the group
+    pub snapshot_count: u64,
+    /// Sum of all snapshot sizes in bytes
+    pub logical_bytes: u64,
+    /// Size of the latest snapshot in bytes
+    pub latest_snapshot_bytes: u64,
+    /// Latest snapshot size minus the size of the newest snapshot that is at
+    /// least the growth window old; `None` if the group has no snapshot that
+    /// old or growth is disabled
+    pub growth_bytes: Option<i64>,
+}
+
+/// Usage of every exposed backup group of a datastore, sorted by namespace,
+/// backup type and backup ID.
+///
+/// `growth_days` of 0 disables the growth column.
+pub(super) fn usage_rows(
+    datastore: &str,
+    snapshots: &[Snapshot],
+    backup_filter: &BackupFilter,
+    growth_days: u64,
+    now: i64,
+) -> Vec<UsageRow> {
+    let mut groups: HashMap<(&str, &str, &str), Vec<&Snapshot>> = HashMap::new();
+    for snapshot in snapshots {
+        let namespace = snapshot.ns.as_deref().unwrap_or("");
+        if backup_filter.allows_group(
+            datastore,
+            namespace,
+            &snapshot.backup_type,
+            &snapshot.backup_id,
+        ) {
+            groups
+                .entry((namespace, &snapshot.backup_type, &snapshot.backup_id))
+                .or_default()
+                .push(snapshot);
+        }
+    }
+
+    let cutoff = (growth_days > 0)
+        .then(|| now.saturating_sub((growth_days as i64).saturating_mul(SECONDS_PER_DAY)));
+
+    let mut rows: Vec<UsageRow> = groups
+        .into_iter()
+        .filter_map(|((namespace, backup_type, backup_id), mut group)| {
+            group.sort_by_key(|snapshot| snapshot.backup_time);
+            let latest = *group.last()?;
+            let latest_bytes = latest.size.unwrap_or(0);
+
+            let growth_bytes = cutoff.and_then(|cutoff| {
+                group
+                    .iter()
+                    .rev()
+                    .find(|snapshot| snapshot.backup_time <= cutoff)
+                    .map(|baseline| latest_bytes as i64 - baseline.size.unwrap_or(0) as i64)
+            });
+
+            Some(UsageRow {
+                server: None,
+                datastore: datastore.to_string(),
+                namespace: namespace.to_string(),
+                owner: latest.owner.clone().unwrap_or_default(),
+                backup_type: backup_type.to_string(),
+                backup_id: backup_id.to_string(),
+                snapshot_count: group.len() as u64,
+                logical_bytes: group.iter().map(|s| s.size.unwrap_or(0)).sum(),
+                latest_snapshot_bytes: latest_bytes,
+                growth_bytes,
+            })
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        (&a.namespace, &a.backup_type, &a.backup_id).cmp(&(
+            &b.namespace,
+            &b.backup_type,
+            &b.backup_id,
+        ))
+    });
+    rows
+}
+
+/// Render usage rows as CSV with a header line.
+///
+/// The `server` column is only present if any row carries a server label.
+/// An unknown growth is left empty.
+pub fn usage_csv(rows: &[UsageRow]) -> String {
+    let with_server = rows.iter().any(|row| row.server.is_some());
+
+    let mut csv = String::new();
+    if with_server {
+        csv.push_str("server,");
+    }
+    csv.push_str(
+        "datastore,namespace,owner,backup_type,backup_id,snapshot_count,logical_bytes,latest_snapshot_bytes,growth_bytes\n",
+    );
+
+    for row in rows {
+        let mut fields = Vec::with_capacity(10);
+        if with_server {
+            fields.push(csv_field(row.server.as_deref().unwrap_or("")));
+        }
+        fields.extend([
+            csv_field(&row.datastore),
+            csv_field(&row.namespace),
+            csv_field(&row.owner),
+            csv_field(&row.backup_type),
+            csv_field(&row.backup_id),
+            row.snapshot_count.to_string(),
+            row.logical_bytes.to_string(),
+            row.latest_snapshot_bytes.to_string(),
+            row.growth_bytes.map(|g| g.to_string()).unwrap_or_default(),
+        ]);
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break (RFC 4180).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_710_331_200;
+
+    fn snapshot(backup_id: &str, age_days: i64, size: u64) -> Snapshot {
+        Snapshot {
+            backup_type: "vm".to_string(),
+            backup_id: backup_id.to_string(),
+            backup_time: NOW - age_days * SECONDS_PER_DAY,
+            comment: None,
+            size: Some(size),
+            protected: None,
+            verification: None,
+            files: Vec::new(),
+            owner: Some("finance@pbs".to_string()),
+            ns: None,
+        }
+    }
+
+    #[test]
+    fn test_usage_rows_sum_latest_and_growth() {
+        let snapshots = [
+            snapshot("100", 0, 500),
+            snapshot("100", 10, 400),
+            snapshot("100", 40, 300),
+            snapshot("101", 1, 200),
+        ];
+        let rows = usage_rows("backup", &snapshots, &BackupFilter::default(), 30, NOW);
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].backup_id, "100");
+        assert_eq!(rows[0].owner, "finance@pbs");
+        assert_eq!(rows[0].snapshot_count, 3);
+        assert_eq!(rows[0].logical_bytes, 1200);
+        assert_eq!(rows[0].latest_snapshot_bytes, 500);
+        assert_eq!(rows[0].growth_bytes, Some(200));
+        // No snapshot old enough to compare against
+        assert_eq!(rows[1].growth_bytes, None);
+    }
+
+    #[test]
+    fn test_growth_can_be_disabled() {
+        let snapshots = [snapshot("100", 0, 500), snapshot("100", 40, 300)];
+        let rows = usage_rows("backup", &snapshots, &BackupFilter::default(), 0, NOW);
+        assert_eq!(rows[0].growth_bytes, None);
+    }
+
+    #[test]
+    fn test_csv_quotes_fields_and_leaves_unknown_growth_empty() {
+        let mut row = usage_rows(
+            "backup",
+            &[snapshot("web,1", 0, 10)],
+            &BackupFilter::default(),
+            30,
+            NOW,
+        )
+        .remove(0);
+        row.owner = "a \"quoted\" owner".to_string();
+
+        let csv = usage_csv(&[row]);
+        let mut lines = csv.lines();
+        assert!(lines
+            .next()
+            .unwrap()
+            .starts_with("datastore,namespace,owner"));
+        assert_eq!(
+            lines.next().unwrap(),
+            "backup,,\"a \"\"quoted\"\" owner\",vm,\"web,1\",1,10,10,"
+        );
+    }
+}
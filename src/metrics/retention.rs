@@ -0,0 +1,255 @@
+//! Retention compliance: expected snapshot buckets per backup group.
+
+use super::filter::GroupPattern;
+use crate::config::RetentionRule;
+use crate::error::{PbsError, Result};
+use std::collections::HashSet;
+
+const SECONDS_PER_HOUR: i64 = 3600;
+const SECONDS_PER_DAY: i64 = 86400;
+
+/// Calendar period a retention count refers to (UTC).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Bucket {
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Bucket {
+    /// `bucket` label value.
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Bucket::Hourly => "hourly",
+            Bucket::Daily => "daily",
+            Bucket::Weekly => "weekly",
+            Bucket::Monthly => "monthly",
+            Bucket::Yearly => "yearly",
+        }
+    }
+
+    /// Sequential index of the period containing `timestamp`, so that
+    /// consecutive periods have consecutive indices.
+    fn period(self, timestamp: i64) -> i64 {
+        let days = timestamp.div_euclid(SECONDS_PER_DAY);
+        match self {
+            Bucket::Hourly => timestamp.div_euclid(SECONDS_PER_HOUR),
+            Bucket::Daily => days,
+            // 1970-01-01 was a Thursday; weeks start on Monday
+            Bucket::Weekly => (days + 3).div_euclid(7),
+            Bucket::Monthly => {
+                let (year, month) = civil_from_days(days);
+                year * 12 + i64::from(month) - 1
+            }
+            Bucket::Yearly => civil_from_days(days).0,
+        }
+    }
+
+    /// Number of the `expected` most recent periods without a snapshot.
+    ///
+    /// The window ends with the period containing `now` if that period already
+    /// has a snapshot, and with the previous period otherwise, so a backup that
+    /// has simply not run yet today is not reported as missing.
+    pub(crate) fn missing(self, expected: u32, backup_times: &[i64], now: i64) -> u32 {
+        let periods: HashSet<i64> = backup_times.iter().map(|&t| self.period(t)).collect();
+        let current = self.period(now);
+        let last = if periods.contains(&current) {
+            current
+        } else {
+            current - 1
+        };
+
+        (0..i64::from(expected))
+            .filter(|offset| !periods.contains(&(last - offset)))
+            .count() as u32
+    }
+}
+
+/// Year and month (1-12) of a day counted from 1970-01-01, in the proleptic
+/// Gregorian calendar (Howard Hinnant's `civil_from_days`).
+fn civil_from_days(days: i64) -> (i64, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Months counted from March, so the leap day is the last day of the year
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month as u32)
+}
+
+/// Compiled [`RetentionRule`].
+struct Rule {
+    groups: GroupPattern,
+    keep: Vec<(Bucket, u32)>,
+}
+
+/// Compiled `[[retention]]` rules, evaluated in configuration order.
+#[derive(Default)]
+pub(crate) struct RetentionRules {
+    rules: Vec<Rule>,
+}
+
+impl RetentionRules {
+    /// Compile the configured rules.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`PbsError::InvalidRule`] for an invalid pattern or a rule without
+    /// any `keep_*` count.
+    pub(crate) fn new(rules: &[RetentionRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let invalid = |message: String| {
+                    PbsError::InvalidRule(format!(
+                        "retention rule {} ({}): {}",
+                        index + 1,
+                        rule.group,
+                        message
+                    ))
+                };
+
+                let keep: Vec<(Bucket, u32)> = [
+                    (Bucket::Hourly, rule.keep_hourly),
+                    (Bucket::Daily, rule.keep_daily),
+                    (Bucket::Weekly, rule.keep_weekly),
+                    (Bucket::Monthly, rule.keep_monthly),
+                    (Bucket::Yearly, rule.keep_yearly),
+                ]
+                .into_iter()
+                .filter(|&(_, count)| count > 0)
+                .collect();
+                if keep.is_empty() {
+                    return Err(invalid("at least one keep_* count must be set".to_string()));
+                }
+
+                Ok(Rule {
+                    groups: GroupPattern::new(
+                        &rule.group,
+                        rule.datastore.as_deref(),
+                        rule.namespace.as_deref(),
+                    )
+                    .map_err(invalid)?,
+                    keep,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Whether no rules are configured.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Expected bucket counts of a group from the first matching rule.
+    pub(crate) fn expected(
+        &self,
+        datastore: &str,
+        namespace: &str,
+        backup_type: &str,
+        backup_id: &str,
+    ) -> Option<&[(Bucket, u32)]> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.groups
+                    .matches(datastore, namespace, backup_type, backup_id)
+            })
+            .map(|rule| rule.keep.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-03-13 12:00:00 UTC, a Wednesday
+    const NOW: i64 = 1_710_331_200;
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1));
+        // 2024-02-29 (leap day) and 2024-03-01
+        assert_eq!(civil_from_days(19_782), (2024, 2));
+        assert_eq!(civil_from_days(19_783), (2024, 3));
+        // 1969-12-31
+        assert_eq!(civil_from_days(-1), (1969, 12));
+    }
+
+    #[test]
+    fn test_weeks_start_on_monday() {
+        let monday = 1_710_115_200; // 2024-03-11 00:00:00 UTC
+        assert_eq!(Bucket::Weekly.period(monday), Bucket::Weekly.period(NOW));
+        assert_eq!(
+            Bucket::Weekly.period(monday - 1),
+            Bucket::Weekly.period(NOW) - 1
+        );
+    }
+
+    #[test]
+    fn test_missing_daily_buckets() {
+        let day = SECONDS_PER_DAY;
+        // Today and the previous two days, then a gap of one day
+        let times = [NOW, NOW - day, NOW - 2 * day, NOW - 4 * day];
+        assert_eq!(Bucket::Daily.missing(3, &times, NOW), 0);
+        assert_eq!(Bucket::Daily.missing(5, &times, NOW), 1);
+        assert_eq!(Bucket::Daily.missing(7, &times, NOW), 3);
+    }
+
+    #[test]
+    fn test_current_period_is_not_required_yet() {
+        let day = SECONDS_PER_DAY;
+        // No backup today yet: the window ends yesterday
+        let times = [NOW - day, NOW - 2 * day];
+        assert_eq!(Bucket::Daily.missing(2, &times, NOW), 0);
+        assert_eq!(Bucket::Daily.missing(3, &times, NOW), 1);
+    }
+
+    #[test]
+    fn test_missing_monthly_and_yearly_buckets() {
+        let times = [NOW, NOW - 31 * SECONDS_PER_DAY];
+        assert_eq!(Bucket::Monthly.missing(2, &times, NOW), 0);
+        assert_eq!(Bucket::Monthly.missing(6, &times, NOW), 4);
+        assert_eq!(Bucket::Yearly.missing(2, &times, NOW), 1);
+    }
+
+    #[test]
+    fn test_rule_without_keep_counts_is_rejected() {
+        let rule = RetentionRule {
+            group: "vm/*".to_string(),
+            datastore: None,
+            namespace: None,
+            keep_hourly: 0,
+            keep_daily: 0,
+            keep_weekly: 0,
+            keep_monthly: 0,
+            keep_yearly: 0,
+        };
+        assert!(RetentionRules::new(std::slice::from_ref(&rule)).is_err());
+
+        let rule = RetentionRule {
+            keep_daily: 7,
+            keep_weekly: 4,
+            ..rule
+        };
+        let rules = RetentionRules::new(&[rule]).unwrap();
+        assert_eq!(
+            rules.expected("backup", "", "vm", "100"),
+            Some(&[(Bucket::Daily, 7), (Bucket::Weekly, 4)][..])
+        );
+        assert_eq!(rules.expected("backup", "", "ct", "100"), None);
+    }
+}
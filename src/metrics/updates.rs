@@ -2,12 +2,17 @@
 //!
 //! This module contains all the functions that update metrics based on PBS API data.
 
-use super::MetricRegistry;
+use super::forecast::UsageSample;
+use super::registry::JobScheduleMetrics;
+use super::{BackupFilter, FreshnessRules, MetricRegistry, RetentionRules, UsageRow};
 use crate::client::{
-    BackupGroup, DatastoreUsage, GcStatus, NodeStatus, Snapshot, TapeDrive, Task,
-    VerificationStatus, VersionInfo,
+    BackupGroup, ChangerEntry, DatastoreStatus, DatastoreUsage, GcStatus, JobScheduleStatus,
+    MediaPool, NodeStatus, PruneJob, Snapshot, SyncJob, TapeBackupJob, TapeChanger, TapeDrive,
+    TapeDriveStatus, TapeMedia, Task, VerificationJob, VerificationStatus, VersionInfo,
 };
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
+use std::time::Duration;
 use tracing::debug;
 
 // Interned strings to avoid repeated allocations
@@ -16,7 +21,23 @@ const EMPTY_STR: &str = "";
 const RUNNING: &str = "running";
 const OK: &str = "ok";
 
-type LatestSnapshotCommentMap = HashMap<(String, String), (i64, Option<String>)>;
+/// Media statuses PBS reports in the tape media inventory.
+const MEDIA_STATUSES: [&str; 5] = ["writable", "full", "unknown", "damaged", "retired"];
+
+/// Snapshot crypt mode of fully encrypted backups.
+const CRYPT_MODE_ENCRYPT: &str = "encrypt";
+
+/// `entry-kind` of drive elements in a changer status.
+const CHANGER_DRIVE: &str = "drive";
+
+/// Minimum number of samples before a fill-rate forecast is exposed.
+const MIN_FORECAST_SAMPLES: usize = 3;
+const SECONDS_PER_DAY: f64 = 86400.0;
+
+/// Offsets into the PBS usage history exposed as `pbs_datastore_usage_history_ratio`.
+const HISTORY_DAYS_AGO: [i64; 3] = [1, 7, 30];
+
+type LatestSnapshotCommentMap = HashMap<(String, String, String), (i64, Option<String>)>;
 
 struct SnapshotVerification {
     value: f64,
@@ -24,33 +45,69 @@ struct SnapshotVerification {
     timestamp: Option<i64>,
 }
 
-pub(super) fn update_node_metrics(metrics: &MetricRegistry, status: &NodeStatus) {
-    debug!("Updating node metrics");
-    metrics.host_cpu_usage.set(status.cpu);
-    metrics.host_io_wait.set(status.wait);
-    metrics.host_load1.set(status.loadavg[0]);
-    metrics.host_load5.set(status.loadavg[1]);
-    metrics.host_load15.set(status.loadavg[2]);
+pub(super) fn update_node_metrics(metrics: &MetricRegistry, node: &str, status: &NodeStatus) {
+    debug!("Updating node metrics for {}", node);
+    let labels = [node];
+    metrics
+        .host_cpu_usage
+        .with_label_values(&labels)
+        .set(status.cpu);
+    metrics
+        .host_io_wait
+        .with_label_values(&labels)
+        .set(status.wait);
+    metrics
+        .host_load1
+        .with_label_values(&labels)
+        .set(status.loadavg[0]);
+    metrics
+        .host_load5
+        .with_label_values(&labels)
+        .set(status.loadavg[1]);
+    metrics
+        .host_load15
+        .with_label_values(&labels)
+        .set(status.loadavg[2]);
     metrics
         .host_memory_used_bytes
+        .with_label_values(&labels)
         .set(status.memory.used as f64);
     metrics
         .host_memory_total_bytes
+        .with_label_values(&labels)
         .set(status.memory.total as f64);
     metrics
         .host_memory_free_bytes
+        .with_label_values(&labels)
         .set(status.memory.free as f64);
-    metrics.host_swap_used_bytes.set(status.swap.used as f64);
-    metrics.host_swap_total_bytes.set(status.swap.total as f64);
-    metrics.host_swap_free_bytes.set(status.swap.free as f64);
-    metrics.host_rootfs_used_bytes.set(status.root.used as f64);
+    metrics
+        .host_swap_used_bytes
+        .with_label_values(&labels)
+        .set(status.swap.used as f64);
+    metrics
+        .host_swap_total_bytes
+        .with_label_values(&labels)
+        .set(status.swap.total as f64);
+    metrics
+        .host_swap_free_bytes
+        .with_label_values(&labels)
+        .set(status.swap.free as f64);
+    metrics
+        .host_rootfs_used_bytes
+        .with_label_values(&labels)
+        .set(status.root.used as f64);
     metrics
         .host_rootfs_total_bytes
+        .with_label_values(&labels)
         .set(status.root.total as f64);
     metrics
         .host_rootfs_avail_bytes
+        .with_label_values(&labels)
         .set(status.root.avail as f64);
-    metrics.host_uptime_seconds.set(status.uptime as f64);
+    metrics
+        .host_uptime_seconds
+        .with_label_values(&labels)
+        .set(status.uptime as f64);
 }
 
 pub(super) fn update_datastore_metrics(metrics: &MetricRegistry, datastores: &[DatastoreUsage]) {
@@ -71,6 +128,169 @@ pub(super) fn update_datastore_metrics(metrics: &MetricRegistry, datastores: &[D
             .datastore_available_bytes
             .with_label_values(&[&ds.store])
             .set(ds.avail as f64);
+
+        // PBS omits the estimate (or reports 0/-1) without enough history
+        if let Some(full_date) = ds.estimated_full_date.filter(|date| *date > 0) {
+            metrics
+                .datastore_estimated_full_timestamp_seconds
+                .with_label_values(&[&ds.store])
+                .set(full_date as f64);
+        }
+
+        update_usage_history_metrics(metrics, ds);
+    }
+}
+
+/// Expose the history points [`HISTORY_DAYS_AGO`] days before the latest one.
+fn update_usage_history_metrics(metrics: &MetricRegistry, ds: &DatastoreUsage) {
+    let (Some(history), Some(delta)) = (&ds.history, ds.history_delta) else {
+        return;
+    };
+    if delta <= 0 || history.is_empty() {
+        return;
+    }
+
+    let latest = history.len() - 1;
+    for days in HISTORY_DAYS_AGO {
+        let steps = (days * 86400 / delta) as usize;
+        let Some(Some(ratio)) = latest.checked_sub(steps).map(|index| history[index]) else {
+            continue;
+        };
+        metrics
+            .datastore_usage_history_ratio
+            .with_label_values(&[ds.store.as_str(), &days.to_string()])
+            .set(ratio);
+    }
+}
+
+/// Expose the exporter's own fill-rate forecast for every datastore.
+///
+/// Growth is a least-squares line through the datastore's usage samples. GC
+/// makes usage a sawtooth: it grows between runs and drops when chunks are
+/// reclaimed. When the window holds enough GC cycles, only the post-GC low
+/// points are fitted, which tracks retained data instead of the sawtooth.
+pub(super) fn update_forecast_metrics(
+    metrics: &MetricRegistry,
+    datastores: &[DatastoreUsage],
+    samples: &HashMap<String, Vec<UsageSample>>,
+    now: f64,
+) {
+    for ds in datastores {
+        let Some(store_samples) = samples.get(&ds.store) else {
+            continue;
+        };
+        let post_gc = post_gc_samples(store_samples);
+        let points = if post_gc.len() >= MIN_FORECAST_SAMPLES {
+            post_gc
+        } else {
+            store_samples.clone()
+        };
+        if points.len() < MIN_FORECAST_SAMPLES {
+            continue;
+        }
+        let Some(fit) = linear_fit(&points) else {
+            continue;
+        };
+
+        debug!(
+            "Forecast for {}: {:.0} bytes/day from {} samples (r² {:.3})",
+            ds.store,
+            fit.slope_per_second * SECONDS_PER_DAY,
+            points.len(),
+            fit.r_squared
+        );
+        let labels = [ds.store.as_str()];
+        metrics
+            .datastore_growth_bytes_per_day
+            .with_label_values(&labels)
+            .set(fit.slope_per_second * SECONDS_PER_DAY);
+        metrics
+            .datastore_forecast_r_squared
+            .with_label_values(&labels)
+            .set(fit.r_squared);
+
+        if fit.slope_per_second > 0.0 {
+            let remaining = ds.total.saturating_sub(ds.used) as f64;
+            metrics
+                .datastore_predicted_full_timestamp_seconds
+                .with_label_values(&labels)
+                .set(now + remaining / fit.slope_per_second);
+        }
+    }
+}
+
+/// Samples taken right after a drop in usage (the low point of each GC run).
+fn post_gc_samples(samples: &[UsageSample]) -> Vec<UsageSample> {
+    samples
+        .windows(2)
+        .enumerate()
+        .filter(|(index, pair)| {
+            let dropped = pair[1].used < pair[0].used;
+            // Keep only the last sample of a run of drops
+            let keeps_dropping = samples
+                .get(index + 2)
+                .is_some_and(|next| next.used < pair[1].used);
+            dropped && !keeps_dropping
+        })
+        .map(|(_, pair)| pair[1])
+        .collect()
+}
+
+/// Least-squares line through the samples.
+struct LinearFit {
+    slope_per_second: f64,
+    /// Coefficient of determination (1 = samples lie on the line)
+    r_squared: f64,
+}
+
+fn linear_fit(samples: &[UsageSample]) -> Option<LinearFit> {
+    let n = samples.len() as f64;
+    // Center timestamps to keep the sums well conditioned
+    let mean_x = samples.iter().map(|s| s.timestamp).sum::<f64>() / n;
+    let mean_y = samples.iter().map(|s| s.used).sum::<f64>() / n;
+
+    let (mut sxx, mut sxy, mut syy) = (0.0, 0.0, 0.0);
+    for sample in samples {
+        let dx = sample.timestamp - mean_x;
+        let dy = sample.used - mean_y;
+        sxx += dx * dx;
+        sxy += dx * dy;
+        syy += dy * dy;
+    }
+    if sxx == 0.0 {
+        return None;
+    }
+
+    let r_squared = if syy == 0.0 {
+        1.0
+    } else {
+        (sxy * sxy) / (sxx * syy)
+    };
+    Some(LinearFit {
+        slope_per_second: sxy / sxx,
+        r_squared,
+    })
+}
+
+pub(super) fn update_datastore_status_metrics(
+    metrics: &MetricRegistry,
+    datastore: &str,
+    status: &DatastoreStatus,
+) {
+    debug!("Updating datastore status metrics for {}", datastore);
+    let Some(counts) = &status.counts else {
+        return;
+    };
+
+    for (backup_type, counts) in counts {
+        metrics
+            .datastore_groups
+            .with_label_values(&[datastore, backup_type])
+            .set(counts.groups as f64);
+        metrics
+            .datastore_snapshots
+            .with_label_values(&[datastore, backup_type])
+            .set(counts.snapshots as f64);
     }
 }
 
@@ -80,6 +300,7 @@ pub(super) fn update_snapshot_metrics(
     snapshots: &[Snapshot],
     comment_map: &LatestSnapshotCommentMap,
     snapshot_history_limit: usize,
+    backup_filter: &BackupFilter,
 ) {
     debug!(
         "Updating individual snapshot metrics for {} snapshots in {}",
@@ -87,28 +308,36 @@ pub(super) fn update_snapshot_metrics(
         datastore
     );
 
-    // Efficient filtering: Sort by Group (Type, ID) then Time Descending
+    // Efficient filtering: Sort by Group (Namespace, Type, ID) then Time Descending
     let mut sorted_snapshots: Vec<_> = snapshots.iter().collect();
     sorted_snapshots.sort_by(|a, b| {
-        a.backup_type
-            .cmp(&b.backup_type)
+        a.ns.cmp(&b.ns)
+            .then_with(|| a.backup_type.cmp(&b.backup_type))
             .then_with(|| a.backup_id.cmp(&b.backup_id))
             .then_with(|| b.backup_time.cmp(&a.backup_time)) // Descending time
     });
 
     let mut exposed_count = 0;
     let mut current_group = None;
+    let mut group_allowed = true;
     let mut group_counter = 0;
 
     for snapshot in sorted_snapshots {
-        let group_key = (&snapshot.backup_type, &snapshot.backup_id);
+        let group_key = (&snapshot.ns, &snapshot.backup_type, &snapshot.backup_id);
 
         if Some(group_key) != current_group {
             current_group = Some(group_key);
             group_counter = 0;
+            group_allowed = backup_filter.allows_group(
+                datastore,
+                snapshot.ns.as_deref().unwrap_or(EMPTY_STR),
+                &snapshot.backup_type,
+                &snapshot.backup_id,
+            );
         }
 
-        if snapshot_history_limit > 0 && group_counter >= snapshot_history_limit {
+        if !group_allowed || (snapshot_history_limit > 0 && group_counter >= snapshot_history_limit)
+        {
             continue;
         }
         group_counter += 1;
@@ -120,10 +349,12 @@ pub(super) fn update_snapshot_metrics(
 
         let timestamp_seconds = snapshot.backup_time;
         let timestamp_str = timestamp_seconds.to_string();
+        let namespace = snapshot.ns.as_deref().unwrap_or(EMPTY_STR);
 
         // Base labels for most metrics
         let base_labels = [
             datastore,
+            namespace,
             &snapshot.backup_type,
             &snapshot.backup_id,
             safe_comment,
@@ -141,6 +372,7 @@ pub(super) fn update_snapshot_metrics(
         // Size metric needs extra "verified" label
         let size_labels = [
             datastore,
+            namespace,
             &snapshot.backup_type,
             &snapshot.backup_id,
             safe_comment,
@@ -192,7 +424,11 @@ fn latest_snapshot_comment<'a>(
     comment_map: &'a LatestSnapshotCommentMap,
 ) -> &'a str {
     // Optimize: clone the key only once for lookup.
-    let lookup_key = (snapshot.backup_type.clone(), snapshot.backup_id.clone());
+    let lookup_key = (
+        snapshot.ns.clone().unwrap_or_default(),
+        snapshot.backup_type.clone(),
+        snapshot.backup_id.clone(),
+    );
 
     comment_map
         .get(&lookup_key)
@@ -238,28 +474,45 @@ pub(super) fn update_backup_metrics(
     datastore: &str,
     groups: &[BackupGroup],
     comment_map: &LatestSnapshotCommentMap,
+    backup_filter: &BackupFilter,
 ) {
     debug!(
         "Updating backup metrics for {} groups in {}",
         groups.len(),
         datastore
     );
+    let mut filtered_groups = 0;
     for group in groups {
+        let namespace = group.ns.as_deref().unwrap_or(EMPTY_STR);
+        if !backup_filter.allows_group(datastore, namespace, &group.backup_type, &group.backup_id) {
+            filtered_groups += 1;
+            continue;
+        }
+
         // Get comment from the latest snapshot via comment_map
         // Optimize: Clone key only once for lookup
-        let lookup_key = (group.backup_type.clone(), group.backup_id.clone());
+        let lookup_key = (
+            group.ns.clone().unwrap_or_default(),
+            group.backup_type.clone(),
+            group.backup_id.clone(),
+        );
         let comment = comment_map
             .get(&lookup_key)
             .and_then(|(_time, comment)| comment.as_deref())
             .unwrap_or(EMPTY_STR);
         let truncated_comment = truncate_backup_comment(comment);
 
-        let labels = &[
+        let mut labels = vec![
             datastore,
+            namespace,
             &group.backup_type,
             &group.backup_id,
             truncated_comment,
         ];
+        if metrics.group_owner_label {
+            labels.push(group.owner.as_deref().unwrap_or(EMPTY_STR));
+        }
+        let labels = labels.as_slice();
 
         metrics
             .snapshot_count
@@ -271,14 +524,239 @@ pub(super) fn update_backup_metrics(
             .with_label_values(labels)
             .set(group.last_backup as f64);
     }
+
+    metrics
+        .backup_groups_filtered
+        .with_label_values(&[datastore])
+        .set(filtered_groups as f64);
+}
+
+/// Evaluate the freshness SLO of every exposed backup group that has one.
+pub(super) fn update_freshness_metrics(
+    metrics: &MetricRegistry,
+    datastore: &str,
+    groups: &[BackupGroup],
+    backup_filter: &BackupFilter,
+    freshness_rules: &FreshnessRules,
+    now: f64,
+) {
+    for group in groups {
+        let namespace = group.ns.as_deref().unwrap_or(EMPTY_STR);
+        if !backup_filter.allows_group(datastore, namespace, &group.backup_type, &group.backup_id) {
+            continue;
+        }
+        let Some(max_age) = freshness_rules.max_age_seconds(
+            datastore,
+            namespace,
+            &group.backup_type,
+            &group.backup_id,
+        ) else {
+            continue;
+        };
+
+        let labels = [datastore, namespace, &group.backup_type, &group.backup_id];
+        let age = (now - group.last_backup as f64).max(0.0);
+        metrics
+            .backup_group_age_seconds
+            .with_label_values(&labels)
+            .set(age);
+        metrics
+            .backup_group_max_age_seconds
+            .with_label_values(&labels)
+            .set(max_age);
+        metrics
+            .backup_group_stale
+            .with_label_values(&labels)
+            .set(if age > max_age { 1.0 } else { 0.0 });
+    }
+}
+
+/// Expose the crypt mode and archive sizes of each exposed backup group's
+/// latest snapshot that lists its files.
+pub(super) fn update_snapshot_file_metrics(
+    metrics: &MetricRegistry,
+    datastore: &str,
+    snapshots: &[Snapshot],
+    backup_filter: &BackupFilter,
+) {
+    let mut latest: HashMap<(&str, &str, &str), &Snapshot> = HashMap::new();
+    for snapshot in snapshots.iter().filter(|s| !s.files.is_empty()) {
+        let key = (
+            snapshot.ns.as_deref().unwrap_or(EMPTY_STR),
+            snapshot.backup_type.as_str(),
+            snapshot.backup_id.as_str(),
+        );
+        match latest.entry(key) {
+            Entry::Occupied(mut entry) => {
+                if snapshot.backup_time > entry.get().backup_time {
+                    entry.insert(snapshot);
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(snapshot);
+            }
+        }
+    }
+
+    for ((namespace, backup_type, backup_id), snapshot) in latest {
+        if !backup_filter.allows_group(datastore, namespace, backup_type, backup_id) {
+            continue;
+        }
+
+        if let Some(crypt_mode) = snapshot.crypt_mode() {
+            let encrypted = if crypt_mode == CRYPT_MODE_ENCRYPT {
+                1.0
+            } else {
+                0.0
+            };
+            metrics
+                .backup_group_encrypted
+                .with_label_values(&[datastore, namespace, backup_type, backup_id, crypt_mode])
+                .set(encrypted);
+        }
+
+        for file in &snapshot.files {
+            if let Some(size) = file.size {
+                metrics
+                    .backup_group_archive_size_bytes
+                    .with_label_values(&[
+                        datastore,
+                        namespace,
+                        backup_type,
+                        backup_id,
+                        &file.filename,
+                    ])
+                    .set(size as f64);
+            }
+        }
+    }
+}
+
+/// Aggregate snapshot counts and sizes of the exposed backup groups per owner.
+pub(super) fn update_owner_metrics(
+    metrics: &MetricRegistry,
+    datastore: &str,
+    snapshots: &[Snapshot],
+    backup_filter: &BackupFilter,
+) {
+    let mut owners: HashMap<&str, (u64, u64)> = HashMap::new();
+    for snapshot in snapshots {
+        if !backup_filter.allows_group(
+            datastore,
+            snapshot.ns.as_deref().unwrap_or(EMPTY_STR),
+            &snapshot.backup_type,
+            &snapshot.backup_id,
+        ) {
+            continue;
+        }
+        let (count, bytes) = owners
+            .entry(snapshot.owner.as_deref().unwrap_or(EMPTY_STR))
+            .or_default();
+        *count += 1;
+        *bytes += snapshot.size.unwrap_or(0);
+    }
+
+    for (owner, (count, bytes)) in owners {
+        metrics
+            .owner_snapshot_count
+            .with_label_values(&[datastore, owner])
+            .set(count as f64);
+        metrics
+            .owner_bytes
+            .with_label_values(&[datastore, owner])
+            .set(bytes as f64);
+    }
+}
+
+/// Expose the logical usage of every backup group in the usage report.
+pub(super) fn update_usage_metrics(metrics: &MetricRegistry, rows: &[UsageRow]) {
+    for row in rows {
+        let labels = [
+            row.datastore.as_str(),
+            row.namespace.as_str(),
+            row.owner.as_str(),
+            row.backup_type.as_str(),
+            row.backup_id.as_str(),
+        ];
+        metrics
+            .usage_logical_bytes
+            .with_label_values(&labels)
+            .set(row.logical_bytes as f64);
+        metrics
+            .usage_latest_snapshot_bytes
+            .with_label_values(&labels)
+            .set(row.latest_snapshot_bytes as f64);
+        if let Some(growth) = row.growth_bytes {
+            metrics
+                .usage_growth_bytes
+                .with_label_values(&labels)
+                .set(growth as f64);
+        }
+    }
+}
+
+/// Count the expected retention periods without a snapshot for every exposed
+/// backup group that has a retention rule.
+pub(super) fn update_retention_metrics(
+    metrics: &MetricRegistry,
+    datastore: &str,
+    snapshots: &[Snapshot],
+    backup_filter: &BackupFilter,
+    retention_rules: &RetentionRules,
+    now: i64,
+) {
+    let mut backup_times: HashMap<(&str, &str, &str), Vec<i64>> = HashMap::new();
+    for snapshot in snapshots {
+        backup_times
+            .entry((
+                snapshot.ns.as_deref().unwrap_or(EMPTY_STR),
+                &snapshot.backup_type,
+                &snapshot.backup_id,
+            ))
+            .or_default()
+            .push(snapshot.backup_time);
+    }
+
+    for ((namespace, backup_type, backup_id), times) in &backup_times {
+        if !backup_filter.allows_group(datastore, namespace, backup_type, backup_id) {
+            continue;
+        }
+        let Some(expected) = retention_rules.expected(datastore, namespace, backup_type, backup_id)
+        else {
+            continue;
+        };
+
+        for &(bucket, count) in expected {
+            let labels = [
+                datastore,
+                namespace,
+                backup_type,
+                backup_id,
+                bucket.as_str(),
+            ];
+            metrics
+                .retention_expected_buckets
+                .with_label_values(&labels)
+                .set(count as f64);
+            metrics
+                .retention_missing_buckets
+                .with_label_values(&labels)
+                .set(bucket.missing(count, times, now) as f64);
+        }
+    }
 }
 
 pub(super) fn update_task_metrics(
     metrics: &MetricRegistry,
+    node: &str,
     tasks: &[Task],
     comment_map: &HashMap<String, String>,
 ) {
-    debug!("Updating task metrics for {} tasks", tasks.len());
+    debug!(
+        "Updating task metrics for {} tasks on {}",
+        tasks.len(),
+        node
+    );
 
     // Count tasks by type and status and comment - use &str to avoid clones
     // Pre-allocate with estimated capacity
@@ -318,13 +796,13 @@ pub(super) fn update_task_metrics(
 
             metrics
                 .task_duration_seconds
-                .with_label_values(&[task.worker_type.as_str(), status, worker_id, comment])
+                .with_label_values(&[task.worker_type.as_str(), status, worker_id, comment, node])
                 .set(duration as f64);
 
             // Update last run timestamp
             metrics
                 .task_last_run_timestamp
-                .with_label_values(&[&task.worker_type])
+                .with_label_values(&[&task.worker_type, node])
                 .set(endtime as f64);
         }
     }
@@ -333,7 +811,7 @@ pub(super) fn update_task_metrics(
     for ((worker_type, status, comment), count) in task_counts {
         metrics
             .task_total
-            .with_label_values(&[worker_type, status, comment])
+            .with_label_values(&[worker_type, status, comment, node])
             .set(count as f64);
     }
 
@@ -341,7 +819,7 @@ pub(super) fn update_task_metrics(
     for ((worker_type, comment), count) in running_counts {
         metrics
             .task_running
-            .with_label_values(&[worker_type, comment])
+            .with_label_values(&[worker_type, comment, node])
             .set(count as f64);
     }
 }
@@ -377,6 +855,48 @@ pub(super) fn update_gc_metrics(metrics: &MetricRegistry, datastore: &str, gc_st
             .set(pending as f64);
     }
 
+    if let Some(index_bytes) = gc_status.index_data_bytes {
+        metrics
+            .gc_index_data_bytes
+            .with_label_values(&[datastore])
+            .set(index_bytes as f64);
+    }
+
+    if let Some(disk_bytes) = gc_status.disk_bytes {
+        metrics
+            .gc_disk_bytes
+            .with_label_values(&[datastore])
+            .set(disk_bytes as f64);
+    }
+
+    if let Some(disk_chunks) = gc_status.disk_chunks {
+        metrics
+            .gc_disk_chunks
+            .with_label_values(&[datastore])
+            .set(disk_chunks as f64);
+    }
+
+    if let Some(index_files) = gc_status.index_file_count {
+        metrics
+            .gc_index_file_count
+            .with_label_values(&[datastore])
+            .set(index_files as f64);
+    }
+
+    if let Some(removed_chunks) = gc_status.removed_chunks {
+        metrics
+            .gc_removed_chunks
+            .with_label_values(&[datastore])
+            .set(removed_chunks as f64);
+    }
+
+    if let Some(ratio) = gc_status.deduplication_ratio() {
+        metrics
+            .datastore_deduplication_ratio
+            .with_label_values(&[datastore])
+            .set(ratio);
+    }
+
     if let Some(state) = &gc_status.last_run_state {
         let status_value = if state.eq_ignore_ascii_case(OK) {
             1.0
@@ -390,6 +910,120 @@ pub(super) fn update_gc_metrics(metrics: &MetricRegistry, datastore: &str, gc_st
     }
 }
 
+pub(super) fn update_sync_job_metrics(metrics: &MetricRegistry, jobs: &[SyncJob]) {
+    debug!("Updating sync job metrics for {} jobs", jobs.len());
+
+    for job in jobs {
+        metrics
+            .sync_job_info
+            .with_label_values(&[
+                job.id.as_str(),
+                job.store.as_str(),
+                job.ns.as_deref().unwrap_or(EMPTY_STR),
+                job.remote.as_deref().unwrap_or(EMPTY_STR),
+                job.remote_store.as_str(),
+                job.remote_ns.as_deref().unwrap_or(EMPTY_STR),
+            ])
+            .set(1.0);
+
+        update_job_schedule_metrics(&metrics.sync_jobs, &job.id, &job.store, &job.status);
+    }
+}
+
+pub(super) fn update_verify_job_metrics(metrics: &MetricRegistry, jobs: &[VerificationJob]) {
+    debug!("Updating verify job metrics for {} jobs", jobs.len());
+
+    for job in jobs {
+        metrics
+            .verify_job_info
+            .with_label_values(&[
+                job.id.as_str(),
+                job.store.as_str(),
+                job.ns.as_deref().unwrap_or(EMPTY_STR),
+                job.schedule.as_deref().unwrap_or(EMPTY_STR),
+            ])
+            .set(1.0);
+
+        let labels = [job.id.as_str(), job.store.as_str()];
+        if let Some(days) = job.outdated_after {
+            metrics
+                .verify_job_outdated_after_seconds
+                .with_label_values(&labels)
+                .set(days as f64 * SECONDS_PER_DAY);
+        }
+        // PBS omits unset options and skips verified snapshots by default
+        let ignore_verified = job.ignore_verified.unwrap_or(true);
+        metrics
+            .verify_job_ignore_verified
+            .with_label_values(&labels)
+            .set(if ignore_verified { 1.0 } else { 0.0 });
+
+        update_job_schedule_metrics(&metrics.verify_jobs, &job.id, &job.store, &job.status);
+    }
+}
+
+pub(super) fn update_prune_job_metrics(metrics: &MetricRegistry, jobs: &[PruneJob]) {
+    debug!("Updating prune job metrics for {} jobs", jobs.len());
+
+    for job in jobs {
+        let namespace = job.ns.as_deref().unwrap_or(EMPTY_STR);
+        metrics
+            .prune_job_info
+            .with_label_values(&[
+                job.id.as_str(),
+                job.store.as_str(),
+                namespace,
+                job.schedule.as_deref().unwrap_or(EMPTY_STR),
+            ])
+            .set(1.0);
+
+        for (keep, count) in job.keep.configured() {
+            metrics
+                .prune_job_keep
+                .with_label_values(&[job.id.as_str(), job.store.as_str(), namespace, keep])
+                .set(count as f64);
+        }
+
+        update_job_schedule_metrics(&metrics.prune_jobs, &job.id, &job.store, &job.status);
+    }
+}
+
+/// Set a job's last run, status, duration and next run gauges where PBS reports them.
+fn update_job_schedule_metrics(
+    metrics: &JobScheduleMetrics,
+    job_id: &str,
+    datastore: &str,
+    status: &JobScheduleStatus,
+) {
+    let labels = [job_id, datastore];
+    if let Some(timestamp) = status.last_run_endtime {
+        metrics
+            .last_run_timestamp_seconds
+            .with_label_values(&labels)
+            .set(timestamp as f64);
+    }
+    if let Some(state) = &status.last_run_state {
+        let status_value = if state.eq_ignore_ascii_case(OK) {
+            1.0
+        } else {
+            0.0
+        };
+        metrics.status.with_label_values(&labels).set(status_value);
+    }
+    if let Some(duration) = status.last_run_duration() {
+        metrics
+            .duration_seconds
+            .with_label_values(&labels)
+            .set(duration as f64);
+    }
+    if let Some(timestamp) = status.next_run {
+        metrics
+            .next_run_timestamp_seconds
+            .with_label_values(&labels)
+            .set(timestamp as f64);
+    }
+}
+
 pub(super) fn update_tape_metrics(metrics: &MetricRegistry, drives: &[TapeDrive]) {
     debug!("Updating tape metrics for {} drives", drives.len());
 
@@ -407,6 +1041,220 @@ pub(super) fn update_tape_metrics(metrics: &MetricRegistry, drives: &[TapeDrive]
     }
 }
 
+pub(super) fn update_tape_drive_state_metrics(
+    metrics: &MetricRegistry,
+    drives: &[TapeDrive],
+    statuses: &[(String, TapeDriveStatus)],
+    changers: &[(String, Vec<ChangerEntry>)],
+) {
+    debug!(
+        "Updating tape drive state metrics for {} drives",
+        drives.len()
+    );
+
+    for drive in drives {
+        let name = drive.name.as_str();
+        let status = statuses
+            .iter()
+            .find(|(drive_name, _)| drive_name == name)
+            .map(|(_, status)| status);
+
+        metrics
+            .tape_drive_busy
+            .with_label_values(&[name])
+            .set(if drive.state.is_some() { 1.0 } else { 0.0 });
+
+        let activity = status
+            .and_then(|status| status.drive_activity.as_deref())
+            .or(drive.activity.as_deref());
+        if let Some(activity) = activity {
+            metrics
+                .tape_drive_activity
+                .with_label_values(&[name, activity])
+                .set(1.0);
+        }
+
+        // Only the changer knows which medium sits in a drive without reading its label
+        let changer_drive = drive.changer.as_deref().zip(drive.changer_drivenum);
+        let slot = changer_drive.and_then(|(changer, drivenum)| {
+            changers
+                .iter()
+                .find(|(changer_name, _)| changer_name == changer)?
+                .1
+                .iter()
+                .find(|entry| entry.entry_kind == CHANGER_DRIVE && entry.entry_id == drivenum)
+        });
+        if let Some(slot) = slot {
+            metrics
+                .tape_drive_media_loaded
+                .with_label_values(&[name, slot.label_text.as_deref().unwrap_or(EMPTY_STR)])
+                .set(if slot.is_occupied() { 1.0 } else { 0.0 });
+        }
+
+        let Some(status) = status else {
+            continue;
+        };
+        if let Some(compression) = status.compression {
+            metrics
+                .tape_drive_compression
+                .with_label_values(&[name])
+                .set(if compression { 1.0 } else { 0.0 });
+        }
+        if let Some(write_protect) = status.write_protect {
+            metrics
+                .tape_drive_write_protected
+                .with_label_values(&[name])
+                .set(if write_protect { 1.0 } else { 0.0 });
+        }
+        for flag in status.alerts() {
+            metrics
+                .tape_drive_alert
+                .with_label_values(&[name, flag])
+                .set(1.0);
+        }
+    }
+}
+
+pub(super) fn update_tape_changer_metrics(
+    metrics: &MetricRegistry,
+    changers: &[TapeChanger],
+    statuses: &[(String, Vec<ChangerEntry>)],
+) {
+    debug!(
+        "Updating tape changer metrics for {} changers",
+        changers.len()
+    );
+
+    for changer in changers {
+        let name = changer.name.as_str();
+        metrics
+            .tape_changer_info
+            .with_label_values(&[
+                name,
+                changer.vendor.as_deref().unwrap_or(UNKNOWN),
+                changer.model.as_deref().unwrap_or(UNKNOWN),
+                changer.serial.as_deref().unwrap_or(UNKNOWN),
+            ])
+            .set(1.0);
+
+        let Some((_, entries)) = statuses
+            .iter()
+            .find(|(changer_name, _)| changer_name == name)
+        else {
+            continue;
+        };
+        let mut slots: HashMap<&str, (u64, u64)> = HashMap::new();
+        for entry in entries {
+            let (total, occupied) = slots.entry(entry.entry_kind.as_str()).or_default();
+            *total += 1;
+            if entry.is_occupied() {
+                *occupied += 1;
+            }
+        }
+        for (kind, (total, occupied)) in slots {
+            metrics
+                .tape_changer_slots
+                .with_label_values(&[name, kind])
+                .set(total as f64);
+            metrics
+                .tape_changer_slots_occupied
+                .with_label_values(&[name, kind])
+                .set(occupied as f64);
+        }
+    }
+}
+
+pub(super) fn update_tape_backup_job_metrics(metrics: &MetricRegistry, jobs: &[TapeBackupJob]) {
+    debug!("Updating tape backup job metrics for {} jobs", jobs.len());
+
+    for job in jobs {
+        metrics
+            .tape_backup_job_info
+            .with_label_values(&[
+                job.id.as_str(),
+                job.store.as_str(),
+                job.ns.as_deref().unwrap_or(EMPTY_STR),
+                job.pool.as_str(),
+                job.drive.as_str(),
+                job.schedule.as_deref().unwrap_or(EMPTY_STR),
+            ])
+            .set(1.0);
+
+        update_job_schedule_metrics(&metrics.tape_backup_jobs, &job.id, &job.store, &job.status);
+    }
+}
+
+pub(super) fn update_media_pool_metrics(metrics: &MetricRegistry, pools: &[MediaPool]) {
+    debug!("Updating media pool metrics for {} pools", pools.len());
+
+    for pool in pools {
+        metrics
+            .tape_media_pool_info
+            .with_label_values(&[
+                pool.name.as_str(),
+                pool.allocation.as_deref().unwrap_or(EMPTY_STR),
+                pool.retention.as_deref().unwrap_or(EMPTY_STR),
+            ])
+            .set(1.0);
+        metrics
+            .tape_media_pool_encrypted
+            .with_label_values(&[pool.name.as_str()])
+            .set(if pool.encrypt.is_some() { 1.0 } else { 0.0 });
+
+        // Expose every status so "no writable media left" is a 0, not a missing series
+        for status in MEDIA_STATUSES {
+            metrics
+                .tape_media_pool_media
+                .with_label_values(&[pool.name.as_str(), status])
+                .set(0.0);
+        }
+        metrics
+            .tape_media_pool_bytes_used
+            .with_label_values(&[pool.name.as_str()])
+            .set(0.0);
+    }
+}
+
+pub(super) fn update_tape_media_metrics(metrics: &MetricRegistry, media: &[TapeMedia]) {
+    debug!("Updating tape media metrics for {} media", media.len());
+
+    for medium in media {
+        let pool = medium.pool.as_deref().unwrap_or(EMPTY_STR);
+        metrics
+            .tape_media_info
+            .with_label_values(&[
+                medium.label_text.as_str(),
+                pool,
+                medium.media_set_name.as_deref().unwrap_or(EMPTY_STR),
+                medium.location.as_str(),
+                medium.status.as_str(),
+            ])
+            .set(1.0);
+
+        let labels = [medium.label_text.as_str(), pool];
+        metrics
+            .tape_media_expired
+            .with_label_values(&labels)
+            .set(if medium.expired { 1.0 } else { 0.0 });
+
+        metrics
+            .tape_media_pool_media
+            .with_label_values(&[pool, medium.status.as_str()])
+            .inc();
+
+        if let Some(bytes) = medium.bytes_used {
+            metrics
+                .tape_media_bytes_used
+                .with_label_values(&labels)
+                .set(bytes as f64);
+            metrics
+                .tape_media_pool_bytes_used
+                .with_label_values(&[pool])
+                .add(bytes as f64);
+        }
+    }
+}
+
 pub(super) fn update_version_metrics(metrics: &MetricRegistry, version: &VersionInfo) {
     debug!("Updating version metrics: {}", version.version);
     metrics
@@ -414,3 +1262,27 @@ pub(super) fn update_version_metrics(metrics: &MetricRegistry, version: &Version
         .with_label_values(&[&version.version, &version.release, &version.repoid])
         .set(1.0);
 }
+
+pub(super) fn update_collector_metrics<'a>(
+    metrics: &MetricRegistry,
+    outcomes: impl Iterator<Item = (&'a str, &'a str, bool, Duration)>,
+) {
+    // Per-datastore collectors report one outcome per datastore; their
+    // durations are summed into a single series per collector.
+    let mut durations: HashMap<&str, f64> = HashMap::new();
+
+    for (collector, datastore, success, duration) in outcomes {
+        metrics
+            .collector_success
+            .with_label_values(&[collector, datastore])
+            .set(if success { 1.0 } else { 0.0 });
+        *durations.entry(collector).or_insert(0.0) += duration.as_secs_f64();
+    }
+
+    for (collector, duration) in durations {
+        metrics
+            .collector_duration_seconds
+            .with_label_values(&[collector])
+            .set(duration);
+    }
+}
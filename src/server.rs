@@ -1,17 +1,23 @@
 //! HTTP server for exposing Prometheus metrics.
 //!
 //! This module provides an Axum-based HTTP server that serves the `/metrics`
-//! endpoint for Prometheus scraping and a `/health` endpoint for health checks.
+//! endpoint for Prometheus scraping, a `/probe` endpoint for scraping named
+//! PBS targets, `/report/usage.csv` and `/report/usage.json` usage reports,
+//! and a `/health` endpoint for health checks.
 
+use crate::config::TargetMode;
 use crate::error::Result;
-use crate::metrics::MetricsCollector;
+use crate::metrics::{
+    collect_all, usage_csv, usage_report_all, Collector, MetricsCollector, UsageRow,
+};
 use axum::{
-    extract::State,
-    http::StatusCode,
+    extract::{Query, State},
+    http::{header, StatusCode},
     response::{IntoResponse, Response},
     routing::get,
-    Router,
+    Json, Router,
 };
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::net::TcpListener;
 use tower_http::trace::TraceLayer;
@@ -20,13 +26,25 @@ use tracing::{info, warn};
 /// Shared application state.
 #[derive(Clone)]
 struct AppState {
-    metrics: Arc<MetricsCollector>,
+    /// Default target served on `/metrics`
+    metrics: Option<Arc<MetricsCollector>>,
+    /// Named targets served on `/probe?target=<name>`
+    targets: Arc<BTreeMap<String, MetricsCollector>>,
+    /// All servers merged into `/metrics` in static target mode
+    static_servers: Option<Arc<Vec<MetricsCollector>>>,
 }
 
 /// Start the HTTP server for serving Prometheus metrics.
 ///
-/// Creates and starts an Axum-based HTTP server that exposes three endpoints:
-/// - `/metrics` - Prometheus metrics endpoint (scrapes PBS on each request)
+/// Creates and starts an Axum-based HTTP server that exposes six endpoints:
+/// - `/metrics` - Prometheus metrics endpoint (scrapes PBS on each request, or
+///   serves the last background collection when a collection interval is set).
+///   `collect[]` query parameters (e.g. `/metrics?collect[]=snapshots&collect[]=gc`)
+///   restrict the scrape to those collectors.
+/// - `/probe?target=<name>` - Scrapes a named PBS target; there are none here,
+///   so every probe returns 404 (see [`start_server_with_targets`])
+/// - `/report/usage.csv` and `/report/usage.json` - Logical storage usage per
+///   backup group (`?target=<name>` reports a named target)
 /// - `/health` - Health check endpoint (always returns OK)
 /// - `/` - Root endpoint with HTML information page
 ///
@@ -59,14 +77,7 @@ struct AppState {
 ///
 /// #[tokio::main]
 /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
-///     let config = PbsConfig {
-///         endpoint: "https://pbs.example.com:8007".to_string(),
-///         token_id: "user@pam!token".to_string(),
-///         token_secret: "secret".to_string(),
-///         verify_tls: false,
-///         timeout_seconds: 5,
-///         snapshot_history_limit: 0,
-///     };
+///     let config = PbsConfig::new("https://pbs.example.com:8007", "user@pam!token", "secret");
 ///     let client = PbsClient::new(config)?;
 ///     let metrics = MetricsCollector::new(std::sync::Arc::new(client), 0)?;
 ///     start_server("0.0.0.0:9101", metrics).await?;
@@ -74,18 +85,84 @@ struct AppState {
 /// }
 /// ```
 pub async fn start_server(listen_address: &str, metrics: MetricsCollector) -> Result<()> {
+    start_server_with_targets(
+        listen_address,
+        Some(metrics),
+        BTreeMap::new(),
+        TargetMode::Probe,
+    )
+    .await
+}
+
+/// Start the HTTP server with named PBS targets.
+///
+/// Serves the same endpoints as [`start_server`]; `/probe?target=<name>`
+/// collects the named PBS target into a fresh, per-request registry
+/// (blackbox-exporter style). `collect[]` parameters are
+/// honoured on probes as well.
+///
+/// With [`TargetMode::Probe`], `/metrics` serves only the default collector and
+/// returns 404 when there is none. With [`TargetMode::Static`], `/metrics`
+/// collects the default collector and every target concurrently and merges
+/// them into one response; the collectors should then carry a `pbs_server`
+/// label (see [`MetricsCollector::with_server_label`]).
+///
+/// # Arguments
+///
+/// * `listen_address` - Address to bind to (e.g., "0.0.0.0:9101")
+/// * `metrics` - Optional default collector served on `/metrics`
+/// * `targets` - Collectors for each named target, keyed by target name
+/// * `target_mode` - Whether targets are also exposed on `/metrics`
+///
+/// # Errors
+///
+/// Returns an error if the listen address cannot be bound or a network error
+/// occurs during server operation.
+pub async fn start_server_with_targets(
+    listen_address: &str,
+    metrics: Option<MetricsCollector>,
+    targets: BTreeMap<String, MetricsCollector>,
+    target_mode: TargetMode,
+) -> Result<()> {
+    let static_servers = match target_mode {
+        TargetMode::Probe => None,
+        TargetMode::Static => Some(Arc::new(
+            metrics
+                .iter()
+                .chain(targets.values())
+                .cloned()
+                .collect::<Vec<_>>(),
+        )),
+    };
+
+    // Background loops for every server whose metrics are served from cache
+    let background: Vec<MetricsCollector> = match &static_servers {
+        Some(servers) => servers.to_vec(),
+        None => metrics.iter().cloned().collect(),
+    };
+    for metrics in background {
+        if let Some(interval) = metrics.collection_interval() {
+            tokio::spawn(async move { metrics.run_collection_loop(interval).await });
+        }
+    }
+
     let state = AppState {
-        metrics: Arc::new(metrics),
+        metrics: metrics.map(Arc::new),
+        targets: Arc::new(targets),
+        static_servers,
     };
 
     let app = Router::new()
         .route("/metrics", get(metrics_handler))
+        .route("/probe", get(probe_handler))
+        .route("/report/usage.csv", get(usage_csv_handler))
+        .route("/report/usage.json", get(usage_json_handler))
         .route("/health", get(health_handler))
         .route("/", get(root_handler))
         .layer(TraceLayer::new_for_http())
         .with_state(state);
 
-    info!("Routes configured: /metrics, /health, /");
+    info!("Routes configured: /metrics, /probe, /report/usage.csv, /report/usage.json, /health, /");
     info!("Starting HTTP server on {}", listen_address);
 
     let listener = TcpListener::bind(listen_address).await?;
@@ -102,18 +179,196 @@ pub async fn start_server(listen_address: &str, metrics: MetricsCollector) -> Re
 }
 
 /// Handler for /metrics endpoint.
-async fn metrics_handler(State(state): State<AppState>) -> Response {
+async fn metrics_handler(
+    State(state): State<AppState>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
     info!("=== METRICS HANDLER CALLED ===");
     info!("Received metrics scrape request");
 
-    // Collect fresh metrics
-    if let Err(e) = state.metrics.collect().await {
-        warn!("Failed to collect metrics: {}", e);
-        // Still return metrics, but pbs_up will be 0
+    let requested = match requested_collectors(&params) {
+        Ok(requested) => requested,
+        Err(name) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown collector: {}", name),
+            )
+                .into_response();
+        }
+    };
+
+    if let Some(servers) = &state.static_servers {
+        return encoded_response(collect_all(servers, requested.as_deref()).await);
+    }
+
+    let Some(metrics) = &state.metrics else {
+        return (
+            StatusCode::NOT_FOUND,
+            "No default PBS server configured, use /probe?target=<name>",
+        )
+            .into_response();
+    };
+
+    // Filtered scrapes collect only the requested collectors into a fresh registry
+    if let Some(collectors) = requested {
+        return encoded_response(metrics.collect_fresh(&collectors).await);
+    }
+
+    // Collect fresh metrics unless a background loop keeps them up to date
+    if metrics.collection_interval().is_none() {
+        if let Err(e) = metrics.collect().await {
+            warn!("Failed to collect metrics: {}", e);
+            // Still return metrics, but pbs_up will be 0
+        }
     }
 
     // Encode metrics in Prometheus format
-    match state.metrics.encode() {
+    encoded_response(metrics.encode())
+}
+
+/// Handler for /probe endpoint.
+async fn probe_handler(
+    State(state): State<AppState>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    let Some(target) = params
+        .iter()
+        .find(|(key, _)| key == "target")
+        .map(|(_, value)| value)
+    else {
+        return (StatusCode::BAD_REQUEST, "Missing target parameter").into_response();
+    };
+
+    info!("Received probe request for target {}", target);
+
+    let Some(collector) = state.targets.get(target) else {
+        return (StatusCode::NOT_FOUND, format!("Unknown target: {}", target)).into_response();
+    };
+
+    // Every probe gets its own registry so targets never bleed into each other
+    let collectors = match requested_collectors(&params) {
+        Ok(Some(collectors)) => collectors,
+        Ok(None) => return encoded_response(collector.collect_probe().await),
+        Err(name) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown collector: {}", name),
+            )
+                .into_response();
+        }
+    };
+
+    encoded_response(collector.collect_fresh(&collectors).await)
+}
+
+/// Handler for /report/usage.csv endpoint.
+async fn usage_csv_handler(
+    State(state): State<AppState>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    match usage_report(&state, &params).await {
+        Ok(rows) => (
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "text/csv; charset=utf-8")],
+            usage_csv(&rows),
+        )
+            .into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Handler for /report/usage.json endpoint.
+async fn usage_json_handler(
+    State(state): State<AppState>,
+    Query(params): Query<Vec<(String, String)>>,
+) -> Response {
+    match usage_report(&state, &params).await {
+        Ok(rows) => (StatusCode::OK, Json(rows)).into_response(),
+        Err(response) => response,
+    }
+}
+
+/// Usage report of the `target` query parameter's target, or of the servers
+/// served on `/metrics` when no target is given.
+///
+/// Responds with 404 when a server has the `snapshots` collector disabled and
+/// with 503 when a server has no complete report yet.
+async fn usage_report(
+    state: &AppState,
+    params: &[(String, String)],
+) -> std::result::Result<Vec<UsageRow>, Response> {
+    info!("Received usage report request");
+
+    let servers: Vec<MetricsCollector> = if let Some((_, target)) =
+        params.iter().find(|(key, _)| key == "target")
+    {
+        match state.targets.get(target) {
+            Some(collector) => vec![collector.clone()],
+            None => {
+                return Err(
+                    (StatusCode::NOT_FOUND, format!("Unknown target: {}", target)).into_response(),
+                )
+            }
+        }
+    } else if let Some(servers) = &state.static_servers {
+        servers.to_vec()
+    } else {
+        match &state.metrics {
+            Some(metrics) => vec![metrics.as_ref().clone()],
+            None => {
+                return Err((
+                    StatusCode::NOT_FOUND,
+                    "No default PBS server configured, use /report/usage.csv?target=<name>",
+                )
+                    .into_response())
+            }
+        }
+    };
+
+    if servers
+        .iter()
+        .any(|server| !server.enabled_collectors().contains(&Collector::Snapshots))
+    {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Usage report unavailable: the snapshots collector is disabled",
+        )
+            .into_response());
+    }
+
+    usage_report_all(&servers).await.map_err(|e| {
+        warn!("Failed to build usage report: {}", e);
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("Usage report unavailable: {}", e),
+        )
+            .into_response()
+    })
+}
+
+/// Parse `collect[]` query parameters.
+///
+/// Returns `Ok(None)` when no filter was requested, or the unknown name on error.
+fn requested_collectors(
+    params: &[(String, String)],
+) -> std::result::Result<Option<Vec<Collector>>, String> {
+    let mut collectors = Vec::new();
+    for (key, value) in params {
+        if key != "collect[]" {
+            continue;
+        }
+        let collector = Collector::from_name(value).ok_or_else(|| value.clone())?;
+        if !collectors.contains(&collector) {
+            collectors.push(collector);
+        }
+    }
+
+    Ok((!collectors.is_empty()).then_some(collectors))
+}
+
+/// Turn encoded metrics into an HTTP response.
+fn encoded_response(encoded: Result<String>) -> Response {
+    match encoded {
         Ok(body) => (StatusCode::OK, body).into_response(),
         Err(e) => {
             warn!("Failed to encode metrics: {}", e);
@@ -155,6 +410,8 @@ async fn root_handler() -> Response {
         <p><strong>Endpoints:</strong></p>
         <ul>
             <li><a href="/metrics">/metrics</a> - Prometheus metrics</li>
+            <li>/probe?target=&lt;name&gt; - Prometheus metrics for a named PBS target</li>
+            <li><a href="/report/usage.csv">/report/usage.csv</a> and <a href="/report/usage.json">/report/usage.json</a> - Logical storage usage per backup group</li>
             <li><a href="/health">/health</a> - Health check</li>
         </ul>
     </div>
@@ -3,7 +3,23 @@
 //! These tests use mockito to simulate PBS API responses
 
 use mockito::Server;
-use pbs_exporter::{client::PbsClient, config::PbsConfig, metrics::MetricsCollector};
+use pbs_exporter::{
+    client::PbsClient,
+    config::{
+        CollectorsConfig, FilterRule, FiltersConfig, FreshnessRule, PbsConfig, RetentionRule,
+    },
+    metrics::{Collector, MetricsCollector},
+};
+
+#[test]
+fn test_config_collector_names_resolve() {
+    let names = CollectorsConfig::default().enabled();
+    let collectors: Vec<Collector> = names
+        .iter()
+        .filter_map(|name| Collector::from_name(name))
+        .collect();
+    assert_eq!(collectors, Collector::ALL.to_vec());
+}
 
 /// Helper to create a test PBS config pointing to mock server
 fn create_test_config(server_url: &str) -> PbsConfig {
@@ -14,6 +30,12 @@ fn create_test_config(server_url: &str) -> PbsConfig {
         verify_tls: false,
         timeout_seconds: 5,
         snapshot_history_limit: 0,
+        namespace_max_depth: 7,
+        max_concurrent_requests: 4,
+        nodes: vec!["localhost".to_string()],
+        discover_nodes: false,
+        retry: Default::default(),
+        circuit_breaker: Default::default(),
     }
 }
 
@@ -56,7 +78,7 @@ async fn test_node_status_success() {
     let config = create_test_config(&server.url());
     let client = PbsClient::new(config).unwrap();
 
-    let status = client.get_node_status().await.unwrap();
+    let status = client.get_node_status("localhost").await.unwrap();
 
     assert_eq!(status.cpu, 0.25);
     assert_eq!(status.wait, 0.01);
@@ -203,7 +225,7 @@ async fn test_api_error_handling() {
     let config = create_test_config(&server.url());
     let client = PbsClient::new(config).unwrap();
 
-    let result = client.get_node_status().await;
+    let result = client.get_node_status("localhost").await;
 
     assert!(result.is_err());
     mock.assert_async().await;
@@ -311,3 +333,2198 @@ async fn test_metrics_collection_failure() {
     let metrics_output = collector.encode().unwrap();
     assert!(metrics_output.contains("pbs_up 0"));
 }
+
+#[tokio::test]
+async fn test_concurrent_collections_share_one_pbs_walk() {
+    let mut server = Server::new_async().await;
+
+    // Each endpoint must be hit exactly once even though two scrapes collect
+    let mock_status = server
+        .mock("GET", "/api2/json/nodes/localhost/status")
+        .with_status(200)
+        .with_body(r#"{"data": {"cpu": 0.1, "wait": 0.01, "memory": {"used": 1000, "total": 2000, "free": 1000}, "swap": {"used": 0, "total": 1000, "free": 1000}, "root": {"used": 1000, "total": 2000, "avail": 1000}, "loadavg": [0.1, 0.1, 0.1], "uptime": 100}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": []}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0).unwrap();
+
+    let (first, second) = tokio::join!(collector.collect(), collector.collect());
+    assert!(first.is_ok());
+    assert!(second.is_ok());
+
+    let metrics_output = collector.encode().unwrap();
+    assert!(metrics_output.contains("pbs_up 1"));
+
+    mock_status.assert_async().await;
+    mock_datastores.assert_async().await;
+    mock_version.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_concurrent_collection_failure_is_shared() {
+    let mut server = Server::new_async().await;
+
+    let mock = server
+        .mock("GET", "/api2/json/nodes/localhost/status")
+        .with_status(500)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0).unwrap();
+
+    let (first, second) = tokio::join!(collector.collect(), collector.collect());
+    assert!(first.is_err());
+    assert!(second.is_err());
+
+    // A later collection starts a new walk instead of reusing the old result
+    mock.assert_async().await;
+    assert!(collector.collect().await.is_err());
+}
+
+#[tokio::test]
+async fn test_parallel_datastore_collection_with_single_permit() {
+    let mut server = Server::new_async().await;
+
+    let _mock_status = server
+        .mock("GET", "/api2/json/nodes/localhost/status")
+        .with_status(200)
+        .with_body(r#"{"data": {"cpu": 0.1, "wait": 0.01, "memory": {"used": 1000, "total": 2000, "free": 1000}, "swap": {"used": 0, "total": 1000, "free": 1000}, "root": {"used": 1000, "total": 2000, "avail": 1000}, "loadavg": [0.1, 0.1, 0.1], "uptime": 100}}"#)
+        .create_async()
+        .await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+            {"store": "ds1", "total": 100, "used": 10, "avail": 90},
+            {"store": "ds2", "total": 200, "used": 20, "avail": 180},
+            {"store": "ds3", "total": 300, "used": 30, "avail": 270}
+        ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let mut group_mocks = Vec::new();
+    for (store, id) in [("ds1", "101"), ("ds2", "102"), ("ds3", "103")] {
+        let mock = server
+            .mock("GET", format!("/api2/json/admin/datastore/{}/groups", store).as_str())
+            .with_status(200)
+            .with_body(format!(
+                r#"{{"data": [{{"backup-type": "vm", "backup-id": "{}", "backup-count": 1, "last-backup": 1703635200}}]}}"#,
+                id
+            ))
+            .create_async()
+            .await;
+        group_mocks.push(mock);
+    }
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    // A single permit forces every request through the semaphore one at a time
+    let mut config = create_test_config(&server.url());
+    config.max_concurrent_requests = 1;
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0).unwrap();
+
+    assert!(collector.collect().await.is_ok());
+
+    let metrics_output = collector.encode().unwrap();
+    assert!(metrics_output.contains("pbs_up 1"));
+    for id in ["101", "102", "103"] {
+        assert!(metrics_output.contains(&format!(r#"backup_id="{}""#, id)));
+    }
+    for mock in group_mocks {
+        mock.assert_async().await;
+    }
+}
+
+#[tokio::test]
+async fn test_api_calls_are_counted_per_endpoint_and_outcome() {
+    let mut server = Server::new_async().await;
+
+    let _mock_status = server
+        .mock("GET", "/api2/json/nodes/localhost/status")
+        .with_status(200)
+        .with_body(r#"{"data": {"cpu": 0.1, "wait": 0.01, "memory": {"used": 1000, "total": 2000, "free": 1000}, "swap": {"used": 0, "total": 1000, "free": 1000}, "root": {"used": 1000, "total": 2000, "avail": 1000}, "loadavg": [0.1, 0.1, 0.1], "uptime": 100}}"#)
+        .create_async()
+        .await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 100, "used": 10, "avail": 90}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_groups = server
+        .mock("GET", "/api2/json/admin/datastore/backup/groups")
+        .with_status(403)
+        .with_body(r#"{"error": "forbidden"}"#)
+        .create_async()
+        .await;
+
+    let _mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body("not json")
+        .create_async()
+        .await;
+
+    let _mock_gc = server
+        .mock("GET", "/api2/json/admin/datastore/backup/gc")
+        .with_status(503)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(&server.url());
+    // Count single attempts; retries are covered separately
+    config.retry.max_retries = 0;
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0).unwrap();
+
+    collector.collect().await.unwrap();
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    // Counters accumulate across collections
+    assert!(metrics_output.contains("# TYPE pbs_exporter_api_calls_total counter"));
+    assert!(metrics_output
+        .contains(r#"pbs_exporter_api_calls_total{endpoint="node_status",outcome="success"} 2"#));
+    assert!(metrics_output
+        .contains(r#"pbs_exporter_api_calls_total{endpoint="groups",outcome="http_4xx"} 2"#));
+    assert!(metrics_output
+        .contains(r#"pbs_exporter_api_calls_total{endpoint="gc",outcome="http_5xx"} 2"#));
+    assert!(metrics_output
+        .contains(r#"pbs_exporter_api_calls_total{endpoint="snapshots",outcome="parse_error"} 2"#));
+    assert!(metrics_output
+        .contains(r#"pbs_exporter_api_request_duration_seconds_count{endpoint="version"} 2"#));
+}
+
+#[tokio::test]
+async fn test_discovered_nodes_are_labelled() {
+    let mut server = Server::new_async().await;
+
+    let _mock_nodes = server
+        .mock("GET", "/api2/json/nodes")
+        .with_status(200)
+        .with_body(r#"{"data": [{"node": "pbs1"}, {"node": "pbs2"}]}"#)
+        .create_async()
+        .await;
+
+    for (node, cpu) in [("pbs1", 0.25), ("pbs2", 0.75)] {
+        server
+            .mock("GET", format!("/api2/json/nodes/{}/status", node).as_str())
+            .with_status(200)
+            .with_body(format!(r#"{{"data": {{"cpu": {}, "wait": 0.01, "memory": {{"used": 1000, "total": 2000, "free": 1000}}, "swap": {{"used": 0, "total": 1000, "free": 1000}}, "root": {{"used": 1000, "total": 2000, "avail": 1000}}, "loadavg": [0.1, 0.1, 0.1], "uptime": 100}}}}"#, cpu))
+            .create_async()
+            .await;
+    }
+
+    let _mock_tasks = server
+        .mock("GET", "/api2/json/nodes/pbs2/tasks?limit=50")
+        .with_status(200)
+        .with_body(r#"{"data": [{"upid": "UPID:pbs2:1", "worker_type": "backup", "starttime": 100, "endtime": 160, "status": "OK"}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(&server.url());
+    config.discover_nodes = true;
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Node, Collector::Tasks, Collector::Version]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(r#"pbs_host_cpu_usage{node="pbs1"} 0.25"#));
+    assert!(metrics_output.contains(r#"pbs_host_cpu_usage{node="pbs2"} 0.75"#));
+    assert!(metrics_output
+        .contains(r#"pbs_task_last_run_timestamp{node="pbs2",worker_type="backup"} 160"#));
+    // pbs1 has no tasks endpoint mocked, so the tasks collector reports failure
+    assert!(metrics_output.contains(r#"pbs_collector_success{collector="node",datastore=""} 1"#));
+    assert!(metrics_output.contains(r#"pbs_collector_success{collector="tasks",datastore=""} 0"#));
+}
+
+#[tokio::test]
+async fn test_transient_errors_are_retried() {
+    let mut server = Server::new_async().await;
+
+    let unavailable = server
+        .mock("GET", "/api2/json/version")
+        .with_status(503)
+        .expect(2)
+        .create_async()
+        .await;
+    let available = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(&server.url());
+    config.retry.initial_backoff_ms = 1;
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Version]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    unavailable.assert_async().await;
+    available.assert_async().await;
+    assert!(metrics_output.contains("pbs_up 1"));
+    assert!(metrics_output.contains(r#"pbs_exporter_api_retries_total{endpoint="version"} 2"#));
+}
+
+#[tokio::test]
+async fn test_fatal_errors_are_not_retried() {
+    let mut server = Server::new_async().await;
+
+    let forbidden = server
+        .mock("GET", "/api2/json/version")
+        .with_status(403)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(&server.url());
+    config.retry.initial_backoff_ms = 1;
+    let client = PbsClient::new(config).unwrap();
+
+    assert!(client.get_version().await.is_err());
+    forbidden.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_circuit_breaker_stops_requests_to_failing_pbs() {
+    let mut server = Server::new_async().await;
+
+    let unavailable = server
+        .mock("GET", "/api2/json/version")
+        .with_status(503)
+        .expect(2)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(&server.url());
+    config.retry.max_retries = 0;
+    config.circuit_breaker.failure_threshold = 2;
+    config.circuit_breaker.open_seconds = 3600;
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Version]);
+
+    for _ in 0..4 {
+        let _ = collector.collect().await;
+    }
+    let metrics_output = collector.encode().unwrap();
+
+    // Only the first two requests reach PBS; the rest are rejected locally
+    unavailable.assert_async().await;
+    assert!(metrics_output.contains("pbs_up 0"));
+    assert!(metrics_output.contains("pbs_exporter_circuit_breaker_state 1"));
+    assert!(metrics_output
+        .contains(r#"pbs_exporter_api_calls_total{endpoint="version",outcome="circuit_open"} 2"#));
+}
+
+#[tokio::test]
+async fn test_retries_stop_once_the_circuit_breaker_opens() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // Every request fails with 503; the version request only after the
+    // datastore request has used up its retries and opened the breaker.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let version_requests = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    tokio::spawn({
+        let version_requests = version_requests.clone();
+        async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let version_requests = version_requests.clone();
+                tokio::spawn(async move {
+                    let mut request = [0u8; 1024];
+                    let read = stream.read(&mut request).await.unwrap();
+                    if String::from_utf8_lossy(&request[..read]).contains("/version") {
+                        version_requests.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                        tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+                    }
+                    let _ = stream
+                        .write_all(
+                            b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                        )
+                        .await;
+                });
+            }
+        }
+    });
+
+    let mut config = create_test_config(&url);
+    config.retry.max_retries = 1;
+    config.retry.initial_backoff_ms = 1;
+    config.circuit_breaker.failure_threshold = 1;
+    config.circuit_breaker.open_seconds = 3600;
+    let client = std::sync::Arc::new(PbsClient::new(config).unwrap());
+
+    let version = tokio::spawn({
+        let client = client.clone();
+        async move { client.get_version().await }
+    });
+    assert!(client.get_datastore_usage().await.is_err());
+    assert!(version.await.unwrap().is_err());
+
+    assert_eq!(
+        version_requests.load(std::sync::atomic::Ordering::SeqCst),
+        1
+    );
+    let collector = MetricsCollector::new(client, 0)
+        .unwrap()
+        .with_collectors([Collector::Version]);
+    let metrics_output = collector.encode().unwrap();
+    assert!(metrics_output
+        .contains(r#"pbs_exporter_api_calls_total{endpoint="version",outcome="circuit_open"} 1"#));
+    assert!(!metrics_output.contains(r#"pbs_exporter_api_retries_total{endpoint="version"}"#));
+}
+
+#[tokio::test]
+async fn test_failed_half_open_trial_reopens_the_circuit_breaker() {
+    let mut server = Server::new_async().await;
+
+    let unavailable = server
+        .mock("GET", "/api2/json/version")
+        .with_status(503)
+        .expect(3)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(&server.url());
+    config.retry.max_retries = 1;
+    config.retry.initial_backoff_ms = 1;
+    config.circuit_breaker.failure_threshold = 1;
+    config.circuit_breaker.open_seconds = 1;
+    let client = std::sync::Arc::new(PbsClient::new(config).unwrap());
+    let collector = MetricsCollector::new(client.clone(), 0)
+        .unwrap()
+        .with_collectors([Collector::Version]);
+    let breaker_state = |state: u8| format!("pbs_exporter_circuit_breaker_state {}\n", state);
+
+    // Two failed attempts open the breaker
+    assert!(client.get_version().await.is_err());
+    assert!(collector.encode().unwrap().contains(&breaker_state(1)));
+
+    // After the cooldown the trial fails with a 503, is not retried and re-opens it
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert!(client.get_version().await.is_err());
+    unavailable.assert_async().await;
+    assert!(collector.encode().unwrap().contains(&breaker_state(1)));
+    assert!(matches!(
+        client.get_version().await,
+        Err(pbs_exporter::PbsError::CircuitOpen)
+    ));
+
+    // Once PBS is back, the next trial closes it
+    unavailable.remove_async().await;
+    let _available = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert!(client.get_version().await.is_ok());
+    assert!(collector.encode().unwrap().contains(&breaker_state(0)));
+}
+
+#[tokio::test]
+async fn test_cancelled_half_open_trial_does_not_stick_the_circuit_breaker() {
+    // PBS accepts connections but never answers
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let connections = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    tokio::spawn({
+        let connections = connections.clone();
+        async move {
+            let mut open = Vec::new();
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                connections.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                open.push(stream);
+            }
+        }
+    });
+
+    let mut config = create_test_config(&url);
+    config.timeout_seconds = 1;
+    config.retry.max_retries = 0;
+    config.circuit_breaker.failure_threshold = 1;
+    config.circuit_breaker.open_seconds = 1;
+    let client = PbsClient::new(config).unwrap();
+    let cancelled = std::time::Duration::from_millis(200);
+
+    // A timed out request opens the breaker
+    assert!(client.get_version().await.is_err());
+
+    // After the cooldown the trial is cancelled, like a scrape that times out
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert!(tokio::time::timeout(cancelled, client.get_version())
+        .await
+        .is_err());
+
+    // The next call after another cooldown reaches PBS again
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    assert!(tokio::time::timeout(cancelled, client.get_version())
+        .await
+        .is_err());
+    assert_eq!(connections.load(std::sync::atomic::Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_datastore_status_and_usage_history() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500, "estimated-full-date": 1800000000, "history-start": 1700000000, "history-delta": 86400, "history": [0.25, 0.3, 0.4, 0.45, 0.46, 0.47, 0.48, null, 0.5]}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_status = server
+        .mock("GET", "/api2/json/admin/datastore/backup/status?verbose=true")
+        .with_status(200)
+        .with_body(r#"{"data": {"total": 1000, "used": 500, "avail": 500, "counts": {"vm": {"groups": 3, "snapshots": 42}, "ct": {"groups": 1, "snapshots": 7}}, "gc-status": {"pending-bytes": 64, "last-run-state": "OK"}}}"#)
+        .create_async()
+        .await;
+
+    // The dedicated GC endpoint fails; the embedded GC status is used instead
+    let _mock_gc = server
+        .mock("GET", "/api2/json/admin/datastore/backup/gc")
+        .with_status(403)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([
+            Collector::Datastore,
+            Collector::DatastoreStatus,
+            Collector::Gc,
+            Collector::Version,
+        ]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_datastore_estimated_full_timestamp_seconds{datastore="backup"} 1800000000"#
+    ));
+    assert!(metrics_output
+        .contains(r#"pbs_datastore_usage_history_ratio{datastore="backup",days_ago="7"} 0.3"#));
+    // Gaps and points before the start of the history are skipped
+    assert!(!metrics_output.contains(r#"days_ago="1""#));
+    assert!(!metrics_output.contains(r#"days_ago="30""#));
+    assert!(
+        metrics_output.contains(r#"pbs_datastore_groups{backup_type="vm",datastore="backup"} 3"#)
+    );
+    assert!(metrics_output
+        .contains(r#"pbs_datastore_snapshots{backup_type="ct",datastore="backup"} 7"#));
+    assert!(metrics_output.contains(r#"pbs_gc_pending_bytes{datastore="backup"} 64"#));
+}
+
+#[tokio::test]
+async fn test_gc_chunk_counts_and_deduplication_ratio() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_gc = server
+        .mock("GET", "/api2/json/admin/datastore/backup/gc")
+        .with_status(200)
+        .with_body(r#"{"data": {"index-data-bytes": 5000, "index-file-count": 12, "disk-bytes": 2000, "disk-chunks": 40, "removed-bytes": 100, "removed-chunks": 3, "pending-bytes": 0, "last-run-state": "OK"}}"#)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Gc]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(r#"pbs_gc_index_data_bytes{datastore="backup"} 5000"#));
+    assert!(metrics_output.contains(r#"pbs_gc_index_file_count{datastore="backup"} 12"#));
+    assert!(metrics_output.contains(r#"pbs_gc_disk_bytes{datastore="backup"} 2000"#));
+    assert!(metrics_output.contains(r#"pbs_gc_disk_chunks{datastore="backup"} 40"#));
+    assert!(metrics_output.contains(r#"pbs_gc_removed_chunks{datastore="backup"} 3"#));
+    assert!(metrics_output.contains(r#"pbs_datastore_deduplication_ratio{datastore="backup"} 2.5"#));
+}
+
+/// Value of the first series starting with `prefix` in an encoded registry.
+fn metric_value(output: &str, prefix: &str) -> Option<f64> {
+    output
+        .lines()
+        .find(|line| line.starts_with(prefix))
+        .and_then(|line| line.rsplit(' ').next())
+        .and_then(|value| value.parse().ok())
+}
+
+#[tokio::test]
+async fn test_forecast_fits_post_gc_usage() {
+    let mut server = Server::new_async().await;
+
+    // Daily PBS history with a GC sawtooth: +100 per day, -50 every other day
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let history_start = now - 6 * 86400 - 43200;
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"data": [{{"store": "backup", "total": 1000, "used": 350, "avail": 650, "history-start": {}, "history-delta": 86400, "history": [0.10, 0.20, 0.15, 0.25, 0.20, 0.30, 0.25]}}]}}"#,
+            history_start
+        ))
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Version])
+        .with_forecast_window(168);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    // Only the post-GC points (150, 200, 250) are fitted: 25 bytes per day
+    let growth = metric_value(&metrics_output, "pbs_datastore_growth_bytes_per_day{").unwrap();
+    assert!((growth - 25.0).abs() < 1e-6, "growth {}", growth);
+    let fit = metric_value(&metrics_output, "pbs_datastore_forecast_r_squared{").unwrap();
+    assert!((fit - 1.0).abs() < 1e-9);
+
+    // 650 bytes left at 25 bytes per day
+    let full = metric_value(
+        &metrics_output,
+        "pbs_datastore_predicted_full_timestamp_seconds{",
+    )
+    .unwrap();
+    let days_left = (full - now as f64) / 86400.0;
+    assert!((days_left - 26.0).abs() < 0.01, "days left {}", days_left);
+}
+
+#[tokio::test]
+async fn test_filtered_scrapes_do_not_record_forecast_samples() {
+    let mut server = Server::new_async().await;
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"data": [{{"store": "backup", "total": 1000, "used": 300, "avail": 700, "history-start": {}, "history-delta": 86400, "history": [0.1, 0.2, 0.3]}}]}}"#,
+            now - 3 * 86400
+        ))
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Version])
+        .with_forecast_window(168);
+
+    // A filtered scrape before any full collection has no samples to fit
+    let filtered = collector
+        .collect_fresh(&[Collector::Datastore])
+        .await
+        .unwrap();
+    assert!(!filtered.contains("pbs_datastore_growth_bytes_per_day{"));
+
+    // The full collection records samples, which later filtered scrapes reuse
+    collector.collect().await.unwrap();
+    assert!(collector
+        .encode()
+        .unwrap()
+        .contains("pbs_datastore_growth_bytes_per_day{"));
+    let filtered = collector
+        .collect_fresh(&[Collector::Datastore])
+        .await
+        .unwrap();
+    assert!(filtered.contains("pbs_datastore_growth_bytes_per_day{"));
+}
+
+#[tokio::test]
+async fn test_forecast_disabled_by_default() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 350, "avail": 650, "history-start": 1700000000, "history-delta": 86400, "history": [0.1, 0.2, 0.3]}]}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore]);
+
+    let _ = collector.collect().await;
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(!metrics_output.contains("pbs_datastore_growth_bytes_per_day{"));
+}
+
+#[tokio::test]
+async fn test_sync_job_metrics() {
+    let mut server = Server::new_async().await;
+
+    // Last run started at 0x65000000 (1694498816) and took 90 seconds
+    let _mock_sync = server
+        .mock("GET", "/api2/json/admin/sync")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"id": "offsite", "store": "backup", "remote": "dr-site", "remote-store": "replica",
+                 "ns": "prod", "schedule": "daily", "next-run": 1694585216,
+                 "last-run-upid": "UPID:pbs:000003E8:00000001:00000002:65000000:syncjob:dr-site\\x3areplica:root@pam:",
+                 "last-run-state": "OK", "last-run-endtime": 1694498906},
+                {"id": "local", "store": "archive", "remote-store": "backup",
+                 "last-run-upid": "UPID:pbs:000003E8:00000001:00000003:65000000:syncjob:local:root@pam:",
+                 "last-run-state": "connection refused", "last-run-endtime": 1694498826},
+                {"id": "new", "store": "backup", "remote": "dr-site", "remote-store": "other"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::SyncJobs]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_sync_job_info{datastore="backup",job_id="offsite",namespace="prod",remote="dr-site",remote_namespace="",remote_store="replica"} 1"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_sync_job_info{datastore="archive",job_id="local",namespace="",remote="",remote_namespace="",remote_store="backup"} 1"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_sync_job_last_run_timestamp_seconds{datastore="backup",job_id="offsite"} 1694498906"#
+    ));
+    assert!(
+        metrics_output.contains(r#"pbs_sync_job_status{datastore="backup",job_id="offsite"} 1"#)
+    );
+    assert!(metrics_output.contains(r#"pbs_sync_job_status{datastore="archive",job_id="local"} 0"#));
+    assert!(metrics_output
+        .contains(r#"pbs_sync_job_duration_seconds{datastore="backup",job_id="offsite"} 90"#));
+    assert!(metrics_output
+        .contains(r#"pbs_sync_job_duration_seconds{datastore="archive",job_id="local"} 10"#));
+    assert!(metrics_output.contains(
+        r#"pbs_sync_job_next_run_timestamp_seconds{datastore="backup",job_id="offsite"} 1694585216"#
+    ));
+
+    // A job that never ran only exposes its info series
+    assert!(metrics_output.contains(r#"job_id="new",namespace="",remote="dr-site""#));
+    assert!(!metrics_output.contains(r#"pbs_sync_job_status{datastore="backup",job_id="new"}"#));
+    assert!(
+        metrics_output.contains(r#"pbs_collector_success{collector="sync_jobs",datastore=""} 1"#)
+    );
+}
+
+#[tokio::test]
+async fn test_verify_job_metrics() {
+    let mut server = Server::new_async().await;
+
+    let _mock_verify = server
+        .mock("GET", "/api2/json/admin/verify")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"id": "v-weekly", "store": "backup", "schedule": "sat 02:00",
+                 "outdated-after": 30, "ignore-verified": true, "next-run": 1695340800,
+                 "last-run-upid": "UPID:pbs:000003E8:00000001:00000002:65000000:verificationjob:backup\\x3av-weekly:root@pam:",
+                 "last-run-state": "verification failed - please check the log for details",
+                 "last-run-endtime": 1694502416},
+                {"id": "v-ns", "store": "backup", "ns": "prod"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::VerifyJobs]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_verify_job_info{datastore="backup",job_id="v-weekly",namespace="",schedule="sat 02:00"} 1"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_verify_job_info{datastore="backup",job_id="v-ns",namespace="prod",schedule=""} 1"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_verify_job_outdated_after_seconds{datastore="backup",job_id="v-weekly"} 2592000"#
+    ));
+    assert!(metrics_output
+        .contains(r#"pbs_verify_job_ignore_verified{datastore="backup",job_id="v-weekly"} 1"#));
+    assert!(metrics_output
+        .contains(r#"pbs_verify_job_ignore_verified{datastore="backup",job_id="v-ns"} 1"#));
+    assert!(
+        metrics_output.contains(r#"pbs_verify_job_status{datastore="backup",job_id="v-weekly"} 0"#)
+    );
+    assert!(metrics_output.contains(
+        r#"pbs_verify_job_last_run_timestamp_seconds{datastore="backup",job_id="v-weekly"} 1694502416"#
+    ));
+    assert!(metrics_output
+        .contains(r#"pbs_verify_job_duration_seconds{datastore="backup",job_id="v-weekly"} 3600"#));
+    assert!(metrics_output.contains(
+        r#"pbs_verify_job_next_run_timestamp_seconds{datastore="backup",job_id="v-weekly"} 1695340800"#
+    ));
+    assert!(!metrics_output
+        .contains(r#"pbs_verify_job_outdated_after_seconds{datastore="backup",job_id="v-ns"}"#));
+    assert!(!metrics_output.contains("pbs_sync_job_status{"));
+}
+
+#[tokio::test]
+async fn test_prune_job_metrics() {
+    let mut server = Server::new_async().await;
+
+    let _mock_prune = server
+        .mock("GET", "/api2/json/admin/prune")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"id": "p-prod", "store": "backup", "ns": "prod", "schedule": "daily",
+                 "keep-last": 3, "keep-daily": 7, "keep-weekly": 4, "keep-monthly": 6,
+                 "next-run": 1694563200, "last-run-state": "OK", "last-run-endtime": 1694476830,
+                 "last-run-upid": "UPID:pbs:000003E8:00000001:00000002:64FFAA00:prunejob:backup\\x3ap-prod:root@pam:"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::PruneJobs]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_prune_job_info{datastore="backup",job_id="p-prod",namespace="prod",schedule="daily"} 1"#
+    ));
+    for (keep, count) in [("last", 3), ("daily", 7), ("weekly", 4), ("monthly", 6)] {
+        assert!(metrics_output.contains(&format!(
+            r#"pbs_prune_job_keep{{datastore="backup",job_id="p-prod",keep="{}",namespace="prod"}} {}"#,
+            keep, count
+        )));
+    }
+    // Unset options are not exposed
+    assert!(!metrics_output.contains(r#"keep="hourly""#));
+    assert!(!metrics_output.contains(r#"keep="yearly""#));
+
+    assert!(
+        metrics_output.contains(r#"pbs_prune_job_status{datastore="backup",job_id="p-prod"} 1"#)
+    );
+    assert!(metrics_output
+        .contains(r#"pbs_prune_job_duration_seconds{datastore="backup",job_id="p-prod"} 30"#));
+    assert!(metrics_output.contains(
+        r#"pbs_prune_job_next_run_timestamp_seconds{datastore="backup",job_id="p-prod"} 1694563200"#
+    ));
+}
+
+#[tokio::test]
+async fn test_tape_jobs_pools_and_media() {
+    let mut server = Server::new_async().await;
+
+    let _mock_drives = server
+        .mock("GET", "/api2/json/tape/drive")
+        .with_status(200)
+        .with_body(r#"{"data": [{"name": "drive0", "vendor": "IBM", "model": "ULT3580-TD8", "serial": "D0"}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_drive_status = server
+        .mock("GET", "/api2/json/tape/drive/drive0/status")
+        .with_status(200)
+        .with_body(r#"{"data": {}}"#)
+        .create_async()
+        .await;
+
+    let _mock_changers = server
+        .mock("GET", "/api2/json/tape/changer")
+        .with_status(200)
+        .with_body(r#"{"data": []}"#)
+        .create_async()
+        .await;
+
+    let _mock_jobs = server
+        .mock("GET", "/api2/json/tape/backup")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [{"id": "weekly-tape", "store": "backup", "pool": "weekly", "drive": "drive0",
+                "schedule": "sun 03:00", "next-media-label": "TAPE02L8", "next-run": 1695000000,
+                "last-run-state": "OK", "last-run-endtime": 1694400000}]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_pools = server
+        .mock("GET", "/api2/json/config/media-pool")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"name": "weekly", "allocation": "sun", "retention": "4 weeks", "encrypt": "aa:bb"},
+                {"name": "archive", "allocation": "continue", "retention": "keep"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_media = server
+        .mock("GET", "/api2/json/tape/media/list?update-status=false")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"uuid": "u1", "label-text": "TAPE01L8", "location": "vault-offsite", "status": "full",
+                 "expired": true, "pool": "weekly", "media-set-name": "weekly 2023-09-03", "bytes-used": 12000000000000,
+                 "catalog": true, "ctime": 1690000000},
+                {"uuid": "u2", "label-text": "TAPE02L8", "location": "online-changer0", "status": "writable",
+                 "expired": false, "pool": "weekly", "bytes-used": 1000, "catalog": true, "ctime": 1690000000},
+                {"uuid": "u3", "label-text": "CLN001L1", "location": "offline", "status": "unknown",
+                 "expired": false, "catalog": false, "ctime": 1690000000}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Tape]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_tape_backup_job_info{datastore="backup",drive="drive0",job_id="weekly-tape",namespace="",pool="weekly",schedule="sun 03:00"} 1"#
+    ));
+    assert!(metrics_output
+        .contains(r#"pbs_tape_backup_job_status{datastore="backup",job_id="weekly-tape"} 1"#));
+    assert!(metrics_output.contains(
+        r#"pbs_tape_backup_job_next_run_timestamp_seconds{datastore="backup",job_id="weekly-tape"} 1695000000"#
+    ));
+
+    assert!(metrics_output.contains(
+        r#"pbs_tape_media_pool_info{allocation="sun",pool="weekly",retention="4 weeks"} 1"#
+    ));
+    assert!(metrics_output.contains(r#"pbs_tape_media_pool_encrypted{pool="weekly"} 1"#));
+    assert!(metrics_output.contains(r#"pbs_tape_media_pool_encrypted{pool="archive"} 0"#));
+    assert!(metrics_output.contains(r#"pbs_tape_media_pool_media{pool="weekly",status="full"} 1"#));
+    assert!(
+        metrics_output.contains(r#"pbs_tape_media_pool_media{pool="weekly",status="writable"} 1"#)
+    );
+    // Configured pools without media report zero per status
+    assert!(
+        metrics_output.contains(r#"pbs_tape_media_pool_media{pool="archive",status="writable"} 0"#)
+    );
+    assert!(
+        metrics_output.contains(r#"pbs_tape_media_pool_bytes_used{pool="weekly"} 12000000001000"#)
+    );
+    // Unassigned media are counted under an empty pool
+    assert!(metrics_output.contains(r#"pbs_tape_media_pool_media{pool="",status="unknown"} 1"#));
+
+    assert!(metrics_output.contains(
+        r#"pbs_tape_media_info{label_text="TAPE01L8",location="vault-offsite",media_set="weekly 2023-09-03",pool="weekly",status="full"} 1"#
+    ));
+    assert!(
+        metrics_output.contains(r#"pbs_tape_media_expired{label_text="TAPE01L8",pool="weekly"} 1"#)
+    );
+    assert!(
+        metrics_output.contains(r#"pbs_tape_media_expired{label_text="TAPE02L8",pool="weekly"} 0"#)
+    );
+    assert!(metrics_output.contains(
+        r#"pbs_tape_media_bytes_used{label_text="TAPE01L8",pool="weekly"} 12000000000000"#
+    ));
+    assert!(!metrics_output.contains(r#"pbs_tape_media_bytes_used{label_text="CLN001L1""#));
+
+    assert!(metrics_output.contains("pbs_tape_drive_available 1"));
+    assert!(metrics_output.contains(r#"pbs_collector_success{collector="tape",datastore=""} 1"#));
+}
+
+#[tokio::test]
+async fn test_tape_partial_failure_keeps_other_lists() {
+    let mut server = Server::new_async().await;
+
+    let _mock_drives = server
+        .mock("GET", "/api2/json/tape/drive")
+        .with_status(200)
+        .with_body(r#"{"data": [{"name": "drive0"}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_pools = server
+        .mock("GET", "/api2/json/config/media-pool")
+        .with_status(200)
+        .with_body(r#"{"data": [{"name": "weekly"}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    // Tape backup jobs and media inventory are not mocked and fail
+    let mut config = create_test_config(&server.url());
+    config.retry.max_retries = 0;
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Tape]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains("pbs_tape_drive_available 1"));
+    assert!(metrics_output.contains(r#"pbs_tape_media_pool_encrypted{pool="weekly"} 0"#));
+    assert!(metrics_output.contains(r#"pbs_collector_success{collector="tape",datastore=""} 0"#));
+}
+
+#[tokio::test]
+async fn test_tape_drive_and_changer_state() {
+    let mut server = Server::new_async().await;
+
+    let _mock_drives = server
+        .mock("GET", "/api2/json/tape/drive")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"name": "drive0", "path": "/dev/tape/by-id/d0", "changer": "changer0", "changer-drivenum": 0,
+                 "vendor": "IBM", "model": "ULT3580-TD8", "serial": "D0"},
+                {"name": "drive1", "path": "/dev/tape/by-id/d1", "changer": "changer0", "changer-drivenum": 1,
+                 "state": "UPID:pbs:000003E8:00000001:00000002:65000000:tape-backup-job:backup:root@pam:",
+                 "activity": "writing"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_drive0_status = server
+        .mock("GET", "/api2/json/tape/drive/drive0/status")
+        .with_status(200)
+        .with_body(
+            r#"{"data": {"vendor": "IBM", "product": "ULT3580-TD8", "revision": "K4K0",
+                "blocksize": 0, "buffer-mode": 1, "density": "LTO8", "compression": true,
+                "write-protect": false, "alert-flags": "TapeAlertFlags(CLEAN_NOW)",
+                "drive-activity": "no-activity"}}"#,
+        )
+        .create_async()
+        .await;
+
+    // The busy drive must not be opened
+    let mock_drive1_status = server
+        .mock("GET", "/api2/json/tape/drive/drive1/status")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let _mock_changers = server
+        .mock("GET", "/api2/json/tape/changer")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [{"name": "changer0", "path": "/dev/tape/by-id/c0", "vendor": "IBM", "model": "TS4300"}]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_changer_status = server
+        .mock("GET", "/api2/json/tape/changer/changer0/status")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"entry-kind": "drive", "entry-id": 0, "label-text": "TAPE03L8", "loaded-slot": 3},
+                {"entry-kind": "drive", "entry-id": 1},
+                {"entry-kind": "slot", "entry-id": 1, "label-text": "TAPE01L8"},
+                {"entry-kind": "slot", "entry-id": 2, "label-text": ""},
+                {"entry-kind": "slot", "entry-id": 3},
+                {"entry-kind": "import-export", "entry-id": 4}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let mut _mock_empty_lists = Vec::new();
+    for path in ["/api2/json/tape/backup", "/api2/json/config/media-pool"] {
+        _mock_empty_lists.push(
+            server
+                .mock("GET", path)
+                .with_status(200)
+                .with_body(r#"{"data": []}"#)
+                .create_async()
+                .await,
+        );
+    }
+    let _mock_media = server
+        .mock("GET", "/api2/json/tape/media/list?update-status=false")
+        .with_status(200)
+        .with_body(r#"{"data": []}"#)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Tape]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(r#"pbs_tape_drive_busy{name="drive0"} 0"#));
+    assert!(metrics_output.contains(r#"pbs_tape_drive_busy{name="drive1"} 1"#));
+    assert!(metrics_output
+        .contains(r#"pbs_tape_drive_activity{activity="no-activity",name="drive0"} 1"#));
+    assert!(
+        metrics_output.contains(r#"pbs_tape_drive_activity{activity="writing",name="drive1"} 1"#)
+    );
+    assert!(metrics_output
+        .contains(r#"pbs_tape_drive_media_loaded{label_text="TAPE03L8",name="drive0"} 1"#));
+    assert!(
+        metrics_output.contains(r#"pbs_tape_drive_media_loaded{label_text="",name="drive1"} 0"#)
+    );
+    assert!(metrics_output.contains(r#"pbs_tape_drive_compression{name="drive0"} 1"#));
+    assert!(metrics_output.contains(r#"pbs_tape_drive_write_protected{name="drive0"} 0"#));
+    assert!(metrics_output.contains(r#"pbs_tape_drive_alert{flag="CLEAN_NOW",name="drive0"} 1"#));
+    assert!(!metrics_output.contains(r#"pbs_tape_drive_compression{name="drive1"}"#));
+
+    assert!(metrics_output.contains(
+        r#"pbs_tape_changer_info{model="TS4300",name="changer0",serial="unknown",vendor="IBM"} 1"#
+    ));
+    assert!(metrics_output.contains(r#"pbs_tape_changer_slots{kind="slot",name="changer0"} 3"#));
+    assert!(metrics_output
+        .contains(r#"pbs_tape_changer_slots_occupied{kind="slot",name="changer0"} 2"#));
+    assert!(metrics_output.contains(r#"pbs_tape_changer_slots{kind="drive",name="changer0"} 2"#));
+    assert!(metrics_output
+        .contains(r#"pbs_tape_changer_slots_occupied{kind="drive",name="changer0"} 1"#));
+    assert!(metrics_output
+        .contains(r#"pbs_tape_changer_slots_occupied{kind="import-export",name="changer0"} 0"#));
+
+    assert!(metrics_output.contains(r#"pbs_collector_success{collector="tape",datastore=""} 1"#));
+    mock_drive1_status.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_snapshots_and_groups_from_nested_namespaces() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}, {"ns": "prod"}]}"#)
+        .create_async()
+        .await;
+
+    let mut mocks = Vec::new();
+    for (query, backup_id) in [("", "100"), ("?ns=prod", "200")] {
+        mocks.push(
+            server
+                .mock(
+                    "GET",
+                    format!("/api2/json/admin/datastore/backup/snapshots{}", query).as_str(),
+                )
+                .with_status(200)
+                .with_body(format!(
+                    r#"{{"data": [{{"backup-type": "vm", "backup-id": "{}", "backup-time": 1700000000, "size": 1024}}]}}"#,
+                    backup_id
+                ))
+                .create_async()
+                .await,
+        );
+        mocks.push(
+            server
+                .mock(
+                    "GET",
+                    format!("/api2/json/admin/datastore/backup/groups{}", query).as_str(),
+                )
+                .with_status(200)
+                .with_body(format!(
+                    r#"{{"data": [{{"backup-type": "vm", "backup-id": "{}", "last-backup": 1700000000, "backup-count": 2}}]}}"#,
+                    backup_id
+                ))
+                .create_async()
+                .await,
+        );
+    }
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([
+            Collector::Datastore,
+            Collector::Snapshots,
+            Collector::Groups,
+        ]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_snapshot_count{backup_id="100",backup_type="vm",comment="",datastore="backup",namespace=""} 2"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_snapshot_count{backup_id="200",backup_type="vm",comment="",datastore="backup",namespace="prod"} 2"#
+    ));
+    assert!(metrics_output.contains(r#"backup_id="200",backup_type="vm",comment="",datastore="backup",namespace="prod",timestamp="1700000000""#));
+    assert!(metrics_output
+        .contains(r#"pbs_collector_success{collector="snapshots",datastore="backup"} 1"#));
+    assert!(metrics_output
+        .contains(r#"pbs_collector_success{collector="groups",datastore="backup"} 1"#));
+}
+
+#[tokio::test]
+async fn test_namespace_is_percent_encoded_in_queries() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": "team a&b/c+d#1%"}]}"#)
+        .create_async()
+        .await;
+
+    let mock_snapshots = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/snapshots?ns=team%20a%26b/c%2Bd%231%25",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"backup-type": "vm", "backup-id": "100", "backup-time": 1700000000, "size": 1024}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Snapshots]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    mock_snapshots.assert_async().await;
+    assert!(metrics_output.contains(r#"namespace="team a&b/c+d#1%""#));
+}
+
+#[tokio::test]
+async fn test_node_name_is_percent_encoded_in_paths() {
+    let mut server = Server::new_async().await;
+
+    let mock_status = server
+        .mock("GET", "/api2/json/nodes/pbs%2F1%3Fa/status")
+        .with_status(200)
+        .with_body(r#"{"data": {"cpu": 0.25, "wait": 0.0, "memory": {"used": 1, "total": 2, "free": 1}, "swap": {"used": 0, "total": 0, "free": 0}, "root": {"used": 1, "total": 2, "avail": 1}, "loadavg": [0.0, 0.0, 0.0], "uptime": 1}}"#)
+        .create_async()
+        .await;
+
+    let mock_tasks = server
+        .mock("GET", "/api2/json/nodes/pbs%2F1%3Fa/tasks?limit=5")
+        .with_status(200)
+        .with_body(r#"{"data": []}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+
+    client.get_node_status("pbs/1?a").await.unwrap();
+    client.get_tasks("pbs/1?a", Some(5)).await.unwrap();
+
+    mock_status.assert_async().await;
+    mock_tasks.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_namespace_depth_zero_skips_discovery() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=0",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .expect(0)
+        .create_async()
+        .await;
+
+    let _mock_groups = server
+        .mock("GET", "/api2/json/admin/datastore/backup/groups")
+        .with_status(200)
+        .with_body(r#"{"data": [{"backup-type": "ct", "backup-id": "101", "last-backup": 1700000000, "backup-count": 1}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let mut config = create_test_config(&server.url());
+    config.namespace_max_depth = 0;
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Groups]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    mock_namespaces.assert_async().await;
+    assert!(metrics_output.contains(
+        r#"pbs_snapshot_count{backup_id="101",backup_type="ct",comment="",datastore="backup",namespace=""} 1"#
+    ));
+}
+
+#[tokio::test]
+async fn test_backup_group_filters() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"backup-type": "vm", "backup-id": "web", "backup-time": 1700000000, "size": 1024},
+                {"backup-type": "vm", "backup-id": "test-1", "backup-time": 1700000000, "size": 1024},
+                {"backup-type": "vm", "backup-id": "test-1", "backup-time": 1700086400, "size": 1024},
+                {"backup-type": "ct", "backup-id": "db", "backup-time": 1700000000, "size": 1024}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_groups = server
+        .mock("GET", "/api2/json/admin/datastore/backup/groups")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"backup-type": "vm", "backup-id": "web", "last-backup": 1700000000, "backup-count": 1},
+                {"backup-type": "vm", "backup-id": "test-1", "last-backup": 1700086400, "backup-count": 2},
+                {"backup-type": "ct", "backup-id": "db", "last-backup": 1700000000, "backup-count": 1}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let filters = FiltersConfig {
+        backup_types: FilterRule {
+            include: vec!["vm".to_string()],
+            exclude: vec![],
+        },
+        backup_ids: FilterRule {
+            include: vec![],
+            exclude: vec!["test-*".to_string()],
+        },
+        ..FiltersConfig::default()
+    };
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([
+            Collector::Datastore,
+            Collector::Snapshots,
+            Collector::Groups,
+        ])
+        .with_filters(&filters)
+        .unwrap();
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(r#"backup_id="web""#));
+    assert!(!metrics_output.contains(r#"backup_id="test-1""#));
+    assert!(!metrics_output.contains(r#"backup_id="db""#));
+    assert!(metrics_output.contains(r#"pbs_backup_groups_filtered{datastore="backup"} 2"#));
+}
+
+#[tokio::test]
+async fn test_excluded_datastores_and_namespaces_only_list_groups() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}, {"store": "scratch", "total": 1000, "used": 100, "avail": 900}]}"#)
+        .create_async()
+        .await;
+
+    for store in ["backup", "scratch"] {
+        server
+            .mock(
+                "GET",
+                format!("/api2/json/admin/datastore/{}/namespace?max-depth=7", store).as_str(),
+            )
+            .with_status(200)
+            .with_body(r#"{"data": [{"ns": ""}, {"ns": "test"}]}"#)
+            .create_async()
+            .await;
+    }
+
+    let _mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(r#"{"data": [{"backup-type": "vm", "backup-id": "web", "backup-time": 1700000000, "size": 1024}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_groups = server
+        .mock("GET", "/api2/json/admin/datastore/backup/groups")
+        .with_status(200)
+        .with_body(r#"{"data": [{"backup-type": "vm", "backup-id": "web", "last-backup": 1700000000, "backup-count": 1}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_excluded_groups = server
+        .mock(
+            "GET",
+            mockito::Matcher::Regex(r"^/api2/json/admin/datastore/(backup/groups\?ns=test|scratch/groups)".to_string()),
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"backup-type": "vm", "backup-id": "test-1", "last-backup": 1700000000, "backup-count": 1}, {"backup-type": "vm", "backup-id": "test-2", "last-backup": 1700000000, "backup-count": 1}]}"#)
+        .create_async()
+        .await;
+
+    // Snapshots, status and GC of excluded datastores and namespaces are never fetched
+    let mock_excluded_snapshots = server
+        .mock(
+            "GET",
+            mockito::Matcher::Regex(
+                r"snapshots\?ns=test|^/api2/json/admin/datastore/scratch/(snapshots|status|gc)"
+                    .to_string(),
+            ),
+        )
+        .expect(0)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let filters = FiltersConfig {
+        datastores: FilterRule {
+            include: vec![],
+            exclude: vec!["scratch".to_string()],
+        },
+        namespaces: FilterRule {
+            include: vec![],
+            exclude: vec!["test".to_string()],
+        },
+        ..FiltersConfig::default()
+    };
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([
+            Collector::Datastore,
+            Collector::DatastoreStatus,
+            Collector::Snapshots,
+            Collector::Groups,
+            Collector::Gc,
+        ])
+        .with_filters(&filters)
+        .unwrap();
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    mock_excluded_snapshots.assert_async().await;
+    assert!(metrics_output.contains(r#"backup_id="web""#));
+    assert!(!metrics_output.contains(r#"backup_id="test-1""#));
+    // The groups of the excluded namespace and datastore are counted as filtered
+    assert!(metrics_output.contains(r#"pbs_backup_groups_filtered{datastore="backup"} 2"#));
+    assert!(metrics_output.contains(r#"pbs_backup_groups_filtered{datastore="scratch"} 4"#));
+    // The datastore usage list still covers every datastore
+    assert!(metrics_output.contains(r#"pbs_datastore_total_bytes{datastore="scratch"}"#));
+    assert!(!metrics_output
+        .contains(r#"pbs_collector_success{collector="snapshots",datastore="scratch"}"#));
+}
+
+#[test]
+fn test_invalid_filter_pattern_is_rejected() {
+    let client = PbsClient::new(create_test_config("http://127.0.0.1:1")).unwrap();
+    let filters = FiltersConfig {
+        datastores: FilterRule {
+            include: vec!["re:(".to_string()],
+            exclude: vec![],
+        },
+        ..FiltersConfig::default()
+    };
+
+    let result = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_filters(&filters);
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_backup_group_freshness_slos() {
+    let mut server = Server::new_async().await;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    // vm/100 is 1h old, host/db-1 3h old and ct/101 has no SLO
+    let _mock_groups = server
+        .mock("GET", "/api2/json/admin/datastore/backup/groups")
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"data": [
+                {{"backup-type": "vm", "backup-id": "100", "last-backup": {}, "backup-count": 1}},
+                {{"backup-type": "host", "backup-id": "db-1", "last-backup": {}, "backup-count": 1}},
+                {{"backup-type": "ct", "backup-id": "101", "last-backup": {}, "backup-count": 1}}
+            ]}}"#,
+            now - 3600,
+            now - 3 * 3600,
+            now - 3600
+        ))
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let rules = [
+        FreshnessRule {
+            group: "vm/*".to_string(),
+            datastore: None,
+            namespace: None,
+            max_age_hours: 26.0,
+        },
+        FreshnessRule {
+            group: "host/db-*".to_string(),
+            datastore: None,
+            namespace: None,
+            max_age_hours: 2.0,
+        },
+    ];
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Groups])
+        .with_freshness_rules(&rules)
+        .unwrap();
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_backup_group_stale{backup_id="100",backup_type="vm",datastore="backup",namespace=""} 0"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_backup_group_stale{backup_id="db-1",backup_type="host",datastore="backup",namespace=""} 1"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_backup_group_max_age_seconds{backup_id="db-1",backup_type="host",datastore="backup",namespace=""} 7200"#
+    ));
+    let age = metric_value(
+        &metrics_output,
+        r#"pbs_backup_group_age_seconds{backup_id="db-1",backup_type="host""#,
+    )
+    .unwrap();
+    assert!((10800.0..10900.0).contains(&age));
+    // Groups without a matching rule have no SLO series
+    assert!(!metrics_output.contains(r#"pbs_backup_group_stale{backup_id="101""#));
+}
+
+#[tokio::test]
+async fn test_retention_missing_buckets() {
+    let mut server = Server::new_async().await;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let day = 86400;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    // Daily backups today, yesterday and three days ago: one day is missing
+    let _mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"data": [
+                {{"backup-type": "vm", "backup-id": "100", "backup-time": {}}},
+                {{"backup-type": "vm", "backup-id": "100", "backup-time": {}}},
+                {{"backup-type": "vm", "backup-id": "100", "backup-time": {}}},
+                {{"backup-type": "ct", "backup-id": "101", "backup-time": {}}}
+            ]}}"#,
+            now,
+            now - day,
+            now - 3 * day,
+            now
+        ))
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let rules = [RetentionRule {
+        group: "vm/*".to_string(),
+        datastore: None,
+        namespace: None,
+        keep_hourly: 0,
+        keep_daily: 4,
+        keep_weekly: 0,
+        keep_monthly: 0,
+        keep_yearly: 0,
+    }];
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Snapshots])
+        .with_retention_rules(&rules)
+        .unwrap();
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_retention_expected_buckets{backup_id="100",backup_type="vm",bucket="daily",datastore="backup",namespace=""} 4"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_retention_missing_buckets{backup_id="100",backup_type="vm",bucket="daily",datastore="backup",namespace=""} 1"#
+    ));
+    // Unset keep counts and groups without a rule are not reported
+    assert!(!metrics_output.contains(r#"bucket="weekly""#));
+    assert!(!metrics_output.contains(r#"pbs_retention_missing_buckets{backup_id="101""#));
+}
+
+#[tokio::test]
+async fn test_latest_snapshot_encryption_and_archive_sizes() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    // vm/100 was encrypted in the past but its latest snapshot is not
+    let _mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"backup-type": "vm", "backup-id": "100", "backup-time": 1700000000, "files": [
+                    {"filename": "index.json.blob", "size": 512, "crypt-mode": "none"},
+                    {"filename": "drive-scsi0.img.fidx", "size": 4096, "crypt-mode": "encrypt"}
+                ]},
+                {"backup-type": "vm", "backup-id": "100", "backup-time": 1700086400, "files": [
+                    {"filename": "index.json.blob", "size": 512, "crypt-mode": "none"},
+                    {"filename": "drive-scsi0.img.fidx", "size": 8192, "crypt-mode": "none"}
+                ]},
+                {"backup-type": "ct", "backup-id": "101", "backup-time": 1700000000, "files": [
+                    {"filename": "index.json.blob", "size": 512, "crypt-mode": "none"},
+                    {"filename": "root.pxar.didx", "size": 2048, "crypt-mode": "encrypt"}
+                ]}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Snapshots]);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_backup_group_encrypted{backup_id="100",backup_type="vm",crypt_mode="none",datastore="backup",namespace=""} 0"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_backup_group_encrypted{backup_id="101",backup_type="ct",crypt_mode="encrypt",datastore="backup",namespace=""} 1"#
+    ));
+    // Only the latest snapshot's archives are exposed
+    assert!(metrics_output.contains(
+        r#"pbs_backup_group_archive_size_bytes{archive="drive-scsi0.img.fidx",backup_id="100",backup_type="vm",datastore="backup",namespace=""} 8192"#
+    ));
+    assert!(!metrics_output.contains(r#"crypt_mode="encrypt",datastore="backup",namespace=""} 0"#));
+    assert_eq!(
+        metrics_output
+            .lines()
+            .filter(|line| line.starts_with("pbs_backup_group_archive_size_bytes{"))
+            .count(),
+        4
+    );
+}
+
+#[tokio::test]
+async fn test_owner_aggregates_and_owner_label() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"backup-type": "vm", "backup-id": "100", "backup-time": 1700000000, "size": 1000, "owner": "tenant-a@pbs!backup"},
+                {"backup-type": "vm", "backup-id": "100", "backup-time": 1700086400, "size": 3000, "owner": "tenant-a@pbs!backup"},
+                {"backup-type": "vm", "backup-id": "200", "backup-time": 1700000000, "size": 500, "owner": "tenant-b@pbs"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_groups = server
+        .mock("GET", "/api2/json/admin/datastore/backup/groups")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"backup-type": "vm", "backup-id": "100", "last-backup": 1700086400, "backup-count": 2, "owner": "tenant-a@pbs!backup"},
+                {"backup-type": "vm", "backup-id": "200", "last-backup": 1700000000, "backup-count": 1, "owner": "tenant-b@pbs"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([
+            Collector::Datastore,
+            Collector::Snapshots,
+            Collector::Groups,
+        ])
+        .with_owner_label(true)
+        .unwrap();
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output
+        .contains(r#"pbs_owner_snapshot_count{datastore="backup",owner="tenant-a@pbs!backup"} 2"#));
+    assert!(metrics_output
+        .contains(r#"pbs_owner_bytes{datastore="backup",owner="tenant-a@pbs!backup"} 4000"#));
+    assert!(
+        metrics_output.contains(r#"pbs_owner_bytes{datastore="backup",owner="tenant-b@pbs"} 500"#)
+    );
+    assert!(metrics_output.contains(
+        r#"pbs_snapshot_count{backup_id="200",backup_type="vm",comment="",datastore="backup",namespace="",owner="tenant-b@pbs"} 1"#
+    ));
+}
+
+#[tokio::test]
+async fn test_usage_report_per_backup_group() {
+    let mut server = Server::new_async().await;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let day = 86400;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    // vm/100 has a snapshot older than the 30 day growth window, vm/200 does not
+    let _mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"data": [
+                {{"backup-type": "vm", "backup-id": "100", "backup-time": {}, "size": 1000, "owner": "tenant-a@pbs"}},
+                {{"backup-type": "vm", "backup-id": "100", "backup-time": {}, "size": 2000, "owner": "tenant-a@pbs"}},
+                {{"backup-type": "vm", "backup-id": "100", "backup-time": {}, "size": 3000, "owner": "tenant-a@pbs"}},
+                {{"backup-type": "vm", "backup-id": "200", "backup-time": {}, "size": 500, "owner": "tenant-b@pbs"}}
+            ]}}"#,
+            now - 40 * day,
+            now - 10 * day,
+            now - 3600,
+            now - day
+        ))
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Snapshots])
+        .with_usage_growth_days(30);
+
+    collector.collect().await.unwrap();
+    let metrics_output = collector.encode().unwrap();
+
+    assert!(metrics_output.contains(
+        r#"pbs_usage_logical_bytes{backup_id="100",backup_type="vm",datastore="backup",namespace="",owner="tenant-a@pbs"} 6000"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_usage_latest_snapshot_bytes{backup_id="100",backup_type="vm",datastore="backup",namespace="",owner="tenant-a@pbs"} 3000"#
+    ));
+    assert!(metrics_output.contains(
+        r#"pbs_usage_growth_bytes{backup_id="100",backup_type="vm",datastore="backup",namespace="",owner="tenant-a@pbs"} 2000"#
+    ));
+    assert!(!metrics_output.contains(r#"pbs_usage_growth_bytes{backup_id="200""#));
+
+    let report = collector.usage_report().await.unwrap();
+    assert_eq!(report.len(), 2);
+    assert_eq!(report[0].backup_id, "100");
+    assert_eq!(report[0].snapshot_count, 3);
+    assert_eq!(report[0].growth_bytes, Some(2000));
+    assert_eq!(report[1].owner, "tenant-b@pbs");
+    assert_eq!(report[1].logical_bytes, 500);
+    assert_eq!(report[1].growth_bytes, None);
+}
+
+#[tokio::test]
+async fn test_usage_report_is_cached_from_full_collections() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    // Only the full collection lists snapshots
+    let mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [{"backup-type": "vm", "backup-id": "100", "backup-time": 1700000000, "size": 1000, "owner": "tenant-a@pbs"}]}"#,
+        )
+        .expect(1)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Snapshots]);
+
+    collector.collect().await.unwrap();
+    // A filtered scrape without snapshots must not replace the report
+    collector
+        .collect_fresh(&[Collector::Datastore])
+        .await
+        .unwrap();
+
+    let report = collector.usage_report().await.unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].logical_bytes, 1000);
+    mock_snapshots.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_usage_report_survives_a_failed_snapshot_listing() {
+    let mut server = Server::new_async().await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 1000, "used": 500, "avail": 500}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    let mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [{"backup-type": "vm", "backup-id": "100", "backup-time": 1700000000, "size": 1000, "owner": "tenant-a@pbs"}]}"#,
+        )
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = std::sync::Arc::new(PbsClient::new(config).unwrap());
+    let collector = MetricsCollector::new(client.clone(), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Snapshots]);
+    collector.collect().await.unwrap();
+
+    // The next snapshot listing fails
+    mock_snapshots.remove_async().await;
+    let _mock_broken = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(403)
+        .create_async()
+        .await;
+    collector.collect().await.unwrap();
+
+    // The report of the last complete listing is kept
+    let report = collector.usage_report().await.unwrap();
+    assert_eq!(report.len(), 1);
+    assert_eq!(report[0].owner, "tenant-a@pbs");
+
+    // Without a complete listing, or with the snapshots collector disabled,
+    // there is no report
+    let fresh = MetricsCollector::new(client.clone(), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Snapshots]);
+    assert!(fresh.usage_report().await.is_err());
+    let disabled = MetricsCollector::new(client, 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore]);
+    assert!(disabled.usage_report().await.is_err());
+}
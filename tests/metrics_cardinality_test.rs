@@ -13,6 +13,9 @@ fn create_test_snapshots(backup_type: &str, backup_id: &str, count: usize) -> Ve
             size: Some(1024 * (i as u64 + 1)),
             verification: None,
             protected: Some(false),
+            files: Vec::new(),
+            owner: None,
+            ns: None,
         })
         .collect()
 }
@@ -190,6 +193,9 @@ fn test_snapshot_sorting_order() {
             size: None,
             verification: None,
             protected: None,
+            files: Vec::new(),
+            owner: None,
+            ns: None,
         },
         Snapshot {
             backup_type: "ct".to_string(),
@@ -199,6 +205,9 @@ fn test_snapshot_sorting_order() {
             size: None,
             verification: None,
             protected: None,
+            files: Vec::new(),
+            owner: None,
+            ns: None,
         },
         Snapshot {
             backup_type: "vm".to_string(),
@@ -208,6 +217,9 @@ fn test_snapshot_sorting_order() {
             size: None,
             verification: None,
             protected: None,
+            files: Vec::new(),
+            owner: None,
+            ns: None,
         },
     ];
 
@@ -8,6 +8,10 @@ fn test_gc_status_ok() {
     // Given: A GC status with state "OK"
     let gc_status = GcStatus {
         disk_bytes: Some(10240000),
+        disk_chunks: Some(2500),
+        index_data_bytes: Some(40960000),
+        index_file_count: Some(12),
+        removed_chunks: Some(250),
         last_run_endtime: Some(1000),
         last_run_state: Some("OK".to_string()),
         duration: Some(120.5),
@@ -39,6 +43,10 @@ fn test_gc_status_error() {
     // Given: A GC status with state "ERROR"
     let gc_status = GcStatus {
         disk_bytes: Some(10240000),
+        disk_chunks: Some(2500),
+        index_data_bytes: Some(40960000),
+        index_file_count: Some(12),
+        removed_chunks: Some(250),
         last_run_endtime: Some(1000),
         last_run_state: Some("ERROR".to_string()),
         duration: Some(10.0),
@@ -83,6 +91,10 @@ fn test_gc_status_optional_fields() {
     // Given: A GC status with all optional fields as None
     let minimal_gc = GcStatus {
         disk_bytes: None,
+        disk_chunks: None,
+        index_data_bytes: None,
+        index_file_count: None,
+        removed_chunks: None,
         last_run_endtime: None,
         last_run_state: None,
         duration: None,
@@ -105,6 +117,10 @@ fn test_gc_duration_precision() {
     // Given: A GC status with fractional duration value
     let gc_status = GcStatus {
         disk_bytes: Some(10240000),
+        disk_chunks: Some(2500),
+        index_data_bytes: Some(40960000),
+        index_file_count: Some(12),
+        removed_chunks: Some(250),
         last_run_endtime: Some(1000),
         last_run_state: Some("OK".to_string()),
         duration: Some(123.456),
@@ -123,6 +139,10 @@ fn test_gc_bytes_values() {
     // Given: A GC status with large byte values
     let gc_status = GcStatus {
         disk_bytes: Some(10240000),
+        disk_chunks: Some(2500),
+        index_data_bytes: Some(40960000),
+        index_file_count: Some(12),
+        removed_chunks: Some(250),
         last_run_endtime: Some(1000),
         last_run_state: Some("OK".to_string()),
         duration: Some(60.0),
@@ -135,3 +155,35 @@ fn test_gc_bytes_values() {
     assert_eq!(gc_status.removed_bytes.unwrap(), 1_073_741_824);
     assert_eq!(gc_status.pending_bytes.unwrap(), 536_870_912);
 }
+
+// WGT: Test deduplication ratio divides logical index bytes by disk bytes
+#[test]
+fn test_gc_deduplication_ratio() {
+    // Given: GC statuses with known, empty and missing disk usage
+    let gc_status = GcStatus {
+        disk_bytes: Some(10240000),
+        disk_chunks: Some(2500),
+        index_data_bytes: Some(40960000),
+        index_file_count: Some(12),
+        removed_chunks: Some(250),
+        last_run_endtime: Some(1000),
+        last_run_state: Some("OK".to_string()),
+        duration: Some(60.0),
+        removed_bytes: Some(1024),
+        pending_bytes: Some(512),
+    };
+    let empty_disk = GcStatus {
+        disk_bytes: Some(0),
+        ..gc_status.clone()
+    };
+    let unknown_index = GcStatus {
+        index_data_bytes: None,
+        ..gc_status.clone()
+    };
+
+    // When: Computing the deduplication ratio
+    // Then: Only the complete status yields a ratio
+    assert_eq!(gc_status.deduplication_ratio(), Some(4.0));
+    assert_eq!(empty_disk.deduplication_ratio(), None);
+    assert_eq!(unknown_index.deduplication_ratio(), None);
+}
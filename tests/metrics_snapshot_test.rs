@@ -1,6 +1,6 @@
 //! Tests for snapshot metrics logic.
 
-use pbs_exporter::client::{Snapshot, VerificationStatus};
+use pbs_exporter::client::{Snapshot, SnapshotFile, VerificationStatus};
 
 // WGT: Test snapshot comment selection from multiple snapshots
 #[test]
@@ -15,6 +15,9 @@ fn test_snapshot_comment_selection() {
             size: Some(1024),
             verification: None,
             protected: Some(false),
+            files: Vec::new(),
+            owner: None,
+            ns: None,
         },
         Snapshot {
             backup_type: "vm".to_string(),
@@ -24,6 +27,9 @@ fn test_snapshot_comment_selection() {
             size: Some(2048),
             verification: None,
             protected: Some(false),
+            files: Vec::new(),
+            owner: None,
+            ns: None,
         },
         Snapshot {
             backup_type: "vm".to_string(),
@@ -33,6 +39,9 @@ fn test_snapshot_comment_selection() {
             size: Some(1536),
             verification: None,
             protected: Some(false),
+            files: Vec::new(),
+            owner: None,
+            ns: None,
         },
     ];
 
@@ -77,6 +86,9 @@ fn test_snapshot_verification_status() {
             last_verify: Some(999),
         }),
         protected: Some(false),
+        files: Vec::new(),
+        owner: None,
+        ns: None,
     };
 
     let unverified_snapshot = Snapshot {
@@ -87,6 +99,9 @@ fn test_snapshot_verification_status() {
         size: Some(1024),
         verification: None,
         protected: Some(false),
+        files: Vec::new(),
+        owner: None,
+        ns: None,
     };
 
     let failed_snapshot = Snapshot {
@@ -100,6 +115,9 @@ fn test_snapshot_verification_status() {
             last_verify: Some(999),
         }),
         protected: Some(false),
+        files: Vec::new(),
+        owner: None,
+        ns: None,
     };
 
     // When/Then: Verification status is checked
@@ -123,6 +141,9 @@ fn test_snapshot_protection_status() {
         size: Some(1024),
         verification: None,
         protected: Some(true),
+        files: Vec::new(),
+        owner: None,
+        ns: None,
     };
 
     let unprotected = Snapshot {
@@ -133,6 +154,9 @@ fn test_snapshot_protection_status() {
         size: Some(1024),
         verification: None,
         protected: Some(false),
+        files: Vec::new(),
+        owner: None,
+        ns: None,
     };
 
     let unknown = Snapshot {
@@ -143,6 +167,9 @@ fn test_snapshot_protection_status() {
         size: Some(1024),
         verification: None,
         protected: None,
+        files: Vec::new(),
+        owner: None,
+        ns: None,
     };
 
     // When/Then: Protection status is evaluated
@@ -172,3 +199,64 @@ fn test_comment_truncation() {
         "This is a very long comment that should be trun"
     );
 }
+
+fn snapshot_with_files(files: &[(&str, Option<&str>)]) -> Snapshot {
+    Snapshot {
+        backup_type: "vm".to_string(),
+        backup_id: "100".to_string(),
+        backup_time: 1000,
+        comment: None,
+        size: None,
+        verification: None,
+        protected: None,
+        files: files
+            .iter()
+            .map(|(filename, crypt_mode)| SnapshotFile {
+                filename: filename.to_string(),
+                size: Some(1024),
+                crypt_mode: crypt_mode.map(str::to_string),
+            })
+            .collect(),
+        owner: None,
+        ns: None,
+    }
+}
+
+// WGT: Test the snapshot crypt mode ignores the never-encrypted manifest
+#[test]
+fn test_snapshot_crypt_mode_ignores_manifest() {
+    // Given: An encrypted snapshot whose manifest is unencrypted
+    let snapshot = snapshot_with_files(&[
+        ("index.json.blob", Some("none")),
+        ("qemu-server.conf.blob", Some("encrypt")),
+        ("drive-scsi0.img.fidx", Some("encrypt")),
+    ]);
+
+    // When/Then: The snapshot counts as encrypted
+    assert_eq!(snapshot.crypt_mode(), Some("encrypt"));
+}
+
+// WGT: Test mixed and missing crypt modes
+#[test]
+fn test_snapshot_crypt_mode_mixed_and_unknown() {
+    // Given: Snapshots with mixed, unset and no file crypt modes, and an
+    // encrypted snapshot with a log blob listed without a crypt mode
+    let mixed = snapshot_with_files(&[
+        ("root.pxar.didx", Some("encrypt")),
+        ("catalog.pcat1.didx", Some("sign-only")),
+    ]);
+    let unset = snapshot_with_files(&[("root.pxar.didx", None)]);
+    let no_files = snapshot_with_files(&[]);
+    let encrypted_with_log = snapshot_with_files(&[
+        ("index.json.blob", Some("none")),
+        ("drive-scsi0.img.fidx", Some("encrypt")),
+        ("client.log.blob", None),
+    ]);
+
+    // When/Then: Mixed modes are reported as such; files without a crypt mode
+    // are ignored
+    assert_eq!(mixed.crypt_mode(), Some("mixed"));
+    assert_eq!(unset.crypt_mode(), None);
+    assert_eq!(no_files.crypt_mode(), None);
+    assert_eq!(encrypted_with_log.crypt_mode(), Some("encrypt"));
+}
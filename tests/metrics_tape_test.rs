@@ -1,6 +1,6 @@
 //! Tests for tape drive metrics.
 
-use pbs_exporter::client::TapeDrive;
+use pbs_exporter::client::{ChangerEntry, TapeDrive, TapeDriveStatus};
 
 // WGT: Test tape drive count metric
 #[test]
@@ -12,12 +12,14 @@ fn test_tape_drive_count() {
             vendor: Some("IBM".to_string()),
             model: Some("ULT3580-TD6".to_string()),
             serial: Some("1234567890".to_string()),
+            ..Default::default()
         },
         TapeDrive {
             name: "drive1".to_string(),
             vendor: Some("HP".to_string()),
             model: Some("Ultrium 6-SCSI".to_string()),
             serial: Some("ABCDEF1234".to_string()),
+            ..Default::default()
         },
     ];
 
@@ -37,6 +39,7 @@ fn test_tape_drive_info_labels() {
         vendor: Some("IBM".to_string()),
         model: Some("ULT3580-TD6".to_string()),
         serial: Some("1234567890".to_string()),
+        ..Default::default()
     };
 
     // When: Accessing all fields for metric labels
@@ -56,6 +59,7 @@ fn test_tape_drive_missing_fields() {
         vendor: None,
         model: None,
         serial: None,
+        ..Default::default()
     };
 
     // When: Accessing fields with fallback to "unknown"
@@ -92,18 +96,21 @@ fn test_tape_drive_various_vendors() {
             vendor: Some("IBM".to_string()),
             model: Some("ULT3580-TD6".to_string()),
             serial: Some("IBM001".to_string()),
+            ..Default::default()
         },
         TapeDrive {
             name: "drive1".to_string(),
             vendor: Some("HP".to_string()),
             model: Some("Ultrium 6-SCSI".to_string()),
             serial: Some("HP001".to_string()),
+            ..Default::default()
         },
         TapeDrive {
             name: "drive2".to_string(),
             vendor: Some("Quantum".to_string()),
             model: Some("SuperLoader 3".to_string()),
             serial: Some("Q001".to_string()),
+            ..Default::default()
         },
     ];
 
@@ -127,6 +134,7 @@ fn test_tape_drive_info_metric_value() {
         vendor: Some("IBM".to_string()),
         model: Some("ULT3580-TD6".to_string()),
         serial: Some("1234567890".to_string()),
+        ..Default::default()
     };
 
     // When: Setting the metric value for tape_drive_info
@@ -136,3 +144,56 @@ fn test_tape_drive_info_metric_value() {
     assert_eq!(metric_value, 1.0);
     assert!(!drive.name.is_empty());
 }
+
+// WGT: Test TapeAlert flags are parsed from the PBS flag string
+#[test]
+fn test_tape_drive_alert_flags() {
+    // Given: A drive status with two active TapeAlert flags
+    let status = TapeDriveStatus {
+        alert_flags: Some("TapeAlertFlags(MEDIA_LIFE | CLEAN_NOW)".to_string()),
+        ..Default::default()
+    };
+
+    // When: Listing the active alerts
+    let alerts = status.alerts();
+
+    // Then: Both flag names are returned
+    assert_eq!(alerts, vec!["MEDIA_LIFE", "CLEAN_NOW"]);
+}
+
+// WGT: Test an empty TapeAlert flag set yields no alerts
+#[test]
+fn test_tape_drive_no_alert_flags() {
+    // Given: Drive statuses without alerts, in both forms PBS may report them
+    let empty = TapeDriveStatus {
+        alert_flags: Some("TapeAlertFlags(0x0)".to_string()),
+        ..Default::default()
+    };
+    let missing = TapeDriveStatus::default();
+
+    // When: Listing the active alerts
+    // Then: No alerts are returned
+    assert!(empty.alerts().is_empty());
+    assert!(missing.alerts().is_empty());
+}
+
+// WGT: Test changer elements are occupied when they hold a labelled or unlabelled medium
+#[test]
+fn test_changer_entry_occupancy() {
+    // Given: A labelled slot, an unlabelled slot and an empty import/export slot
+    let entry = |kind: &str, label_text: Option<&str>| ChangerEntry {
+        entry_kind: kind.to_string(),
+        entry_id: 1,
+        label_text: label_text.map(str::to_string),
+        loaded_slot: None,
+    };
+    let labelled = entry("slot", Some("TAPE01L8"));
+    let unlabelled = entry("slot", Some(""));
+    let empty = entry("import-export", None);
+
+    // When: Checking occupancy
+    // Then: Only the empty import/export slot is free
+    assert!(labelled.is_occupied());
+    assert!(unlabelled.is_occupied());
+    assert!(!empty.is_occupied());
+}
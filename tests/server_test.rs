@@ -2,8 +2,12 @@
 
 use mockito::Server;
 use pbs_exporter::{
-    client::PbsClient, config::PbsConfig, metrics::MetricsCollector, server::start_server,
+    client::PbsClient,
+    config::{PbsConfig, TargetMode},
+    metrics::{collect_all, Collector, MetricsCollector},
+    server::{start_server, start_server_with_targets},
 };
+use std::collections::BTreeMap;
 use std::time::Duration;
 
 /// Helper to create test config
@@ -15,6 +19,12 @@ fn create_test_config(server_url: &str) -> PbsConfig {
         verify_tls: false,
         timeout_seconds: 5,
         snapshot_history_limit: 0,
+        namespace_max_depth: 7,
+        max_concurrent_requests: 4,
+        nodes: vec!["localhost".to_string()],
+        discover_nodes: false,
+        retry: Default::default(),
+        circuit_breaker: Default::default(),
     }
 }
 
@@ -362,3 +372,639 @@ async fn test_large_number_of_vms() {
     assert!(metrics.contains(r#"backup_id="100""#));
     assert!(metrics.contains(r#"backup_id="199""#));
 }
+
+// WGT: Test background collection populates metrics without a scrape-triggered collect
+#[tokio::test]
+async fn test_background_collection_loop() {
+    // Given: A mock PBS server and a collector configured for background collection
+    let mut server = Server::new_async().await;
+
+    let _mock_status = server
+        .mock("GET", "/api2/json/nodes/localhost/status")
+        .with_status(200)
+        .with_body(r#"{"data": {"cpu": 0.1, "wait": 0.01, "memory": {"used": 1000, "total": 2000, "free": 1000}, "swap": {"used": 0, "total": 1000, "free": 1000}, "root": {"used": 1000, "total": 2000, "avail": 1000}, "loadavg": [0.1, 0.1, 0.1], "uptime": 100}}"#)
+        .create_async()
+        .await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": []}"#)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collection_interval(3600);
+    let interval = collector.collection_interval().unwrap();
+
+    // When: Running the collection loop in the background
+    let background = collector.clone();
+    let loop_handle = tokio::spawn(async move { background.run_collection_loop(interval).await });
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    // Then: Encoding serves the cached result with a collection timestamp
+    let metrics = collector.encode().unwrap();
+    assert!(metrics.contains("pbs_up 1"));
+    assert!(metrics.contains("pbs_exporter_last_collection_timestamp_seconds"));
+    assert!(!metrics.contains("pbs_exporter_last_collection_timestamp_seconds 0\n"));
+    assert!(metrics.contains("pbs_exporter_last_collection_age_seconds"));
+
+    loop_handle.abort();
+}
+
+// WGT: Test failed collections do not advance the last collection timestamp
+#[tokio::test]
+async fn test_failed_collection_keeps_last_collection_timestamp() {
+    // Given: A PBS server that fails every request
+    let mut server = Server::new_async().await;
+    let _mock_broken = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let client = PbsClient::new(create_test_config(&server.url())).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Version]);
+
+    // When: Collecting
+    let result = collector.collect().await;
+
+    // Then: Only the attempt is recorded, so staleness alerts can fire
+    assert!(result.is_err());
+    let metrics = collector.encode().unwrap();
+    assert!(metrics.contains("pbs_up 0"));
+    assert!(metrics.contains("pbs_exporter_last_collection_timestamp_seconds 0\n"));
+    assert!(!metrics.contains("pbs_exporter_last_collection_attempt_timestamp_seconds 0\n"));
+}
+
+// WGT: Test failed background collections keep serving the last successful data
+#[tokio::test]
+async fn test_failed_collection_keeps_last_successful_data() {
+    for background in [true, false] {
+        // Given: A collector that has completed one successful collection
+        let mut server = Server::new_async().await;
+        let mock_version = server
+            .mock("GET", "/api2/json/version")
+            .with_status(200)
+            .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+            .create_async()
+            .await;
+
+        let client = PbsClient::new(create_test_config(&server.url())).unwrap();
+        let mut collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+            .unwrap()
+            .with_collectors([Collector::Version]);
+        if background {
+            collector = collector.with_collection_interval(3600);
+        }
+        collector.collect().await.unwrap();
+
+        // When: PBS becomes unreachable and the next collection fails
+        mock_version.remove_async().await;
+        let _mock_broken = server
+            .mock("GET", mockito::Matcher::Any)
+            .with_status(500)
+            .create_async()
+            .await;
+        let result = collector.collect().await;
+
+        // Then: Only background mode keeps the cached series; pbs_up reports the outage
+        assert!(result.is_err());
+        let metrics = collector.encode().unwrap();
+        assert!(metrics.contains("pbs_up 0"));
+        assert_eq!(
+            metrics.contains(r#"pbs_version{release="1",repoid="test",version="4.1.0"} 1"#),
+            background
+        );
+        assert!(metrics.contains(r#"pbs_collector_success{collector="version",datastore=""} 0"#));
+    }
+}
+
+// WGT: Test a failed version request alone does not discard fresh data
+#[tokio::test]
+async fn test_version_failure_keeps_fresh_background_data() {
+    // Given: A background collector whose datastore usage changes between collections
+    let mut server = Server::new_async().await;
+    let mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 100, "used": 10, "avail": 90}]}"#)
+        .create_async()
+        .await;
+    let mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let client = PbsClient::new(create_test_config(&server.url())).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Version])
+        .with_collection_interval(3600);
+    collector.collect().await.unwrap();
+
+    // When: Only the version request fails on the next collection
+    mock_datastores.remove_async().await;
+    mock_version.remove_async().await;
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 100, "used": 20, "avail": 80}]}"#)
+        .create_async()
+        .await;
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(500)
+        .create_async()
+        .await;
+    let _ = collector.collect().await;
+
+    // Then: The datastore metrics reflect the new collection
+    let metrics = collector.encode().unwrap();
+    assert!(metrics.contains(r#"pbs_datastore_used_bytes{datastore="backup"} 20"#));
+    assert!(!metrics.contains("pbs_version{"));
+}
+
+// WGT: Test on-scrape mode is the default
+#[tokio::test]
+async fn test_collection_interval_zero_keeps_on_scrape_mode() {
+    // Given: A collector configured with a zero collection interval
+    let config = create_test_config("http://127.0.0.1:1");
+    let client = PbsClient::new(config).unwrap();
+
+    // When: Building the collector
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collection_interval(0);
+
+    // Then: No background interval should be configured
+    assert!(collector.collection_interval().is_none());
+}
+
+// WGT: Test a failing sub-collector is reported without marking PBS down
+#[tokio::test]
+async fn test_collector_success_isolates_failures() {
+    // Given: A mock PBS server where node status and one datastore's snapshots fail
+    let mut server = Server::new_async().await;
+
+    let _mock_status = server
+        .mock("GET", "/api2/json/nodes/localhost/status")
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "store1", "total": 100, "used": 10, "avail": 90}, {"store": "store2", "total": 100, "used": 10, "avail": 90}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces1 = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/store1/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_namespaces2 = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/store2/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_snapshots1 = server
+        .mock("GET", "/api2/json/admin/datastore/store1/snapshots")
+        .with_status(200)
+        .with_body(r#"{"data": []}"#)
+        .create_async()
+        .await;
+
+    let _mock_snapshots2 = server
+        .mock("GET", "/api2/json/admin/datastore/store2/snapshots")
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0).unwrap();
+
+    // When: Collecting metrics
+    let result = collector.collect().await;
+
+    // Then: PBS is up, but the failing collectors are reported individually
+    assert!(result.is_ok());
+    let metrics = collector.encode().unwrap();
+    assert!(metrics.contains("pbs_up 1"));
+    assert!(metrics.contains(r#"pbs_collector_success{collector="node",datastore=""} 0"#));
+    assert!(metrics.contains(r#"pbs_collector_success{collector="datastore",datastore=""} 1"#));
+    assert!(
+        metrics.contains(r#"pbs_collector_success{collector="snapshots",datastore="store1"} 1"#)
+    );
+    assert!(
+        metrics.contains(r#"pbs_collector_success{collector="snapshots",datastore="store2"} 0"#)
+    );
+    assert!(metrics.contains(r#"pbs_collector_success{collector="version",datastore=""} 1"#));
+    assert!(metrics.contains(r#"pbs_collector_duration_seconds{collector="snapshots"}"#));
+    assert!(metrics.contains(r#"pbs_datastore_total_bytes{datastore="store2"}"#));
+}
+
+// WGT: Test filtered collection only queries the requested collectors
+#[tokio::test]
+async fn test_collect_fresh_runs_only_requested_collectors() {
+    // Given: A mock PBS server where node status must not be queried
+    let mut server = Server::new_async().await;
+
+    let mock_status = server
+        .mock("GET", "/api2/json/nodes/localhost/status")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 100, "used": 10, "avail": 90}]}"#)
+        .create_async()
+        .await;
+
+    let _mock_gc = server
+        .mock("GET", "/api2/json/admin/datastore/backup/gc")
+        .with_status(200)
+        .with_body(r#"{"data": {"last-run-state": "OK", "pending-bytes": 42}}"#)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0).unwrap();
+
+    // When: Collecting only the GC collector into a fresh registry
+    let metrics = collector.collect_fresh(&[Collector::Gc]).await.unwrap();
+
+    // Then: Only GC metrics are populated and the shared registry is untouched
+    assert!(metrics.contains("pbs_up 1"));
+    assert!(metrics.contains(r#"pbs_gc_pending_bytes{datastore="backup"} 42"#));
+    assert!(metrics.contains(r#"pbs_collector_success{collector="gc",datastore="backup"} 1"#));
+    assert!(!metrics.contains(r#"pbs_datastore_total_bytes{datastore="backup"}"#));
+    assert!(!metrics.contains(r#"collector="node""#));
+    assert!(collector.encode().unwrap().contains("pbs_up 0"));
+    mock_status.assert_async().await;
+}
+
+// WGT: Test collectors disabled in config are skipped
+#[tokio::test]
+async fn test_disabled_collectors_are_not_queried() {
+    // Given: A collector with tape and snapshot collection disabled
+    let mut server = Server::new_async().await;
+
+    let _mock_status = server
+        .mock("GET", "/api2/json/nodes/localhost/status")
+        .with_status(200)
+        .with_body(r#"{"data": {"cpu": 0.1, "wait": 0.01, "memory": {"used": 1000, "total": 2000, "free": 1000}, "swap": {"used": 0, "total": 1000, "free": 1000}, "root": {"used": 1000, "total": 2000, "avail": 1000}, "loadavg": [0.1, 0.1, 0.1], "uptime": 100}}"#)
+        .create_async()
+        .await;
+
+    let mock_tape = server
+        .mock("GET", "/api2/json/tape/drive")
+        .expect(0)
+        .create_async()
+        .await;
+
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let config = create_test_config(&server.url());
+    let client = PbsClient::new(config).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Node, Collector::Version]);
+
+    // When: Collecting metrics
+    collector.collect().await.unwrap();
+
+    // Then: Disabled collectors neither query PBS nor report an outcome
+    let metrics = collector.encode().unwrap();
+    assert!(metrics.contains(r#"pbs_collector_success{collector="node",datastore=""} 1"#));
+    assert!(!metrics.contains(r#"collector="tape""#));
+    mock_tape.assert_async().await;
+}
+
+// WGT: Test probes collect each named target into its own registry
+#[tokio::test]
+async fn test_probe_endpoint_scrapes_named_targets() {
+    // Given: Two PBS servers registered as probe targets and no default server
+    let mut targets = BTreeMap::new();
+    let mut servers = Vec::new();
+    for (name, version) in [("primary", "4.1.0"), ("offsite", "3.4.2")] {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/api2/json/version")
+            .with_status(200)
+            .with_body(format!(
+                r#"{{"data": {{"version": "{}", "release": "1", "repoid": "test"}}}}"#,
+                version
+            ))
+            .create_async()
+            .await;
+        let client = PbsClient::new(create_test_config(&server.url())).unwrap();
+        let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+            .unwrap()
+            .with_collectors([Collector::Version]);
+        targets.insert(name.to_string(), collector);
+        servers.push(server);
+    }
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let address = format!("127.0.0.1:{}", port);
+    let listen = address.clone();
+    let server_handle = tokio::spawn(async move {
+        start_server_with_targets(&listen, None, targets, TargetMode::Probe).await
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // When: Probing each target and some invalid requests
+    let get = |path: &str| reqwest::get(format!("http://{}{}", address, path));
+    let primary = get("/probe?target=primary").await.unwrap();
+    let primary_status = primary.status();
+    let primary_body = primary.text().await.unwrap();
+    let offsite_body = get("/probe?target=offsite")
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let unknown = get("/probe?target=missing").await.unwrap().status();
+    let no_target = get("/probe").await.unwrap().status();
+    let no_default = get("/metrics").await.unwrap().status();
+
+    // Then: Each probe reports its own server and invalid requests are rejected
+    assert_eq!(primary_status, 200);
+    assert!(primary_body.contains(r#"version="4.1.0""#));
+    assert!(!primary_body.contains(r#"version="3.4.2""#));
+    assert!(offsite_body.contains(r#"version="3.4.2""#));
+    assert_eq!(unknown, 404);
+    assert_eq!(no_target, 400);
+    assert_eq!(no_default, 404);
+
+    server_handle.abort();
+}
+
+// WGT: Test unfiltered probes record forecast samples
+#[tokio::test]
+async fn test_probe_targets_get_forecasts() {
+    // Given: A probe target with forecasting enabled and PBS usage history
+    let mut server = Server::new_async().await;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(format!(
+            r#"{{"data": [{{"store": "backup", "total": 1000, "used": 300, "avail": 700, "history-start": {}, "history-delta": 86400, "history": [0.1, 0.2, 0.3]}}]}}"#,
+            now - 3 * 86400
+        ))
+        .create_async()
+        .await;
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+    let client = PbsClient::new(create_test_config(&server.url())).unwrap();
+    let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore])
+        .with_forecast_window(168);
+    let targets = BTreeMap::from([("primary".to_string(), collector)]);
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let address = format!("127.0.0.1:{}", port);
+    let listen = address.clone();
+    let server_handle = tokio::spawn(async move {
+        start_server_with_targets(&listen, None, targets, TargetMode::Probe).await
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // When: Probing the target with and without collect[] filters
+    let mut bodies = Vec::new();
+    for path in [
+        "/probe?target=primary&collect[]=datastore",
+        "/probe?target=primary",
+        "/probe?target=primary&collect[]=datastore",
+    ] {
+        let response = reqwest::get(format!("http://{}{}", address, path))
+            .await
+            .unwrap();
+        bodies.push(response.text().await.unwrap());
+    }
+    let [filtered_before, unfiltered, filtered_after] = &bodies[..] else {
+        unreachable!()
+    };
+
+    // Then: The unfiltered probe records samples that filtered probes reuse
+    assert!(!filtered_before.contains("pbs_datastore_growth_bytes_per_day{"));
+    assert!(unfiltered.contains("pbs_datastore_growth_bytes_per_day{"));
+    assert!(unfiltered.contains("pbs_datastore_predicted_full_timestamp_seconds{"));
+    assert!(filtered_after.contains("pbs_datastore_growth_bytes_per_day{"));
+
+    server_handle.abort();
+}
+
+// WGT: Test static multi-target mode keeps healthy servers when one is down
+#[tokio::test]
+async fn test_static_targets_are_labelled_and_isolated() {
+    // Given: One healthy PBS server and one that fails every request
+    let mut healthy = Server::new_async().await;
+    let _mock_datastores = healthy
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 100, "used": 10, "avail": 90}]}"#)
+        .create_async()
+        .await;
+    let _mock_version = healthy
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let mut broken = Server::new_async().await;
+    let _mock_broken = broken
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(500)
+        .create_async()
+        .await;
+
+    let mut collectors = Vec::new();
+    for (name, server) in [("primary", &healthy), ("offsite", &broken)] {
+        let client = PbsClient::new(create_test_config(&server.url())).unwrap();
+        let collector = MetricsCollector::new(std::sync::Arc::new(client), 0)
+            .unwrap()
+            .with_collectors([Collector::Datastore, Collector::Version])
+            .with_server_label(name)
+            .unwrap();
+        collectors.push(collector);
+    }
+
+    // When: Collecting all servers into one response
+    let metrics = collect_all(&collectors, None).await.unwrap();
+
+    // Then: Every series is labelled and only the broken server reports down
+    assert!(metrics.contains(r#"pbs_up{pbs_server="primary"} 1"#));
+    assert!(metrics.contains(r#"pbs_up{pbs_server="offsite"} 0"#));
+    assert!(metrics
+        .contains(r#"pbs_datastore_total_bytes{datastore="backup",pbs_server="primary"} 100"#));
+    assert!(!metrics.contains(r#"datastore="backup",pbs_server="offsite""#));
+    assert_eq!(metrics.matches("# HELP pbs_up ").count(), 1);
+}
+
+// WGT: Test usage report endpoints serve CSV and JSON per backup group
+#[tokio::test]
+async fn test_usage_report_endpoints() {
+    // Given: A PBS server with two backup groups of different owners
+    let mut server = Server::new_async().await;
+    let _mock_namespaces = server
+        .mock(
+            "GET",
+            "/api2/json/admin/datastore/backup/namespace?max-depth=7",
+        )
+        .with_status(200)
+        .with_body(r#"{"data": [{"ns": ""}]}"#)
+        .create_async()
+        .await;
+    let _mock_datastores = server
+        .mock("GET", "/api2/json/status/datastore-usage")
+        .with_status(200)
+        .with_body(r#"{"data": [{"store": "backup", "total": 100, "used": 10, "avail": 90}]}"#)
+        .create_async()
+        .await;
+    let _mock_snapshots = server
+        .mock("GET", "/api2/json/admin/datastore/backup/snapshots")
+        .with_status(200)
+        .with_body(
+            r#"{"data": [
+                {"backup-type": "vm", "backup-id": "100", "backup-time": 1700000000, "size": 1000, "owner": "tenant-a@pbs"},
+                {"backup-type": "vm", "backup-id": "100", "backup-time": 1700086400, "size": 3000, "owner": "tenant-a@pbs"},
+                {"backup-type": "ct", "backup-id": "200", "backup-time": 1700000000, "size": 500, "owner": "tenant-b@pbs"}
+            ]}"#,
+        )
+        .create_async()
+        .await;
+    let _mock_version = server
+        .mock("GET", "/api2/json/version")
+        .with_status(200)
+        .with_body(r#"{"data": {"version": "4.1.0", "release": "1", "repoid": "test"}}"#)
+        .create_async()
+        .await;
+
+    let client = std::sync::Arc::new(PbsClient::new(create_test_config(&server.url())).unwrap());
+    let collector = MetricsCollector::new(client.clone(), 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore, Collector::Snapshots]);
+    let without_snapshots = MetricsCollector::new(client, 0)
+        .unwrap()
+        .with_collectors([Collector::Datastore]);
+    let targets = BTreeMap::from([("no-snapshots".to_string(), without_snapshots)]);
+
+    let port = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let address = format!("127.0.0.1:{}", port);
+    let listen = address.clone();
+    let server_handle = tokio::spawn(async move {
+        start_server_with_targets(&listen, Some(collector), targets, TargetMode::Probe).await
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    // When: Requesting the CSV and JSON reports and an unknown target
+    let get = |path: &str| reqwest::get(format!("http://{}{}", address, path));
+    let csv = get("/report/usage.csv").await.unwrap();
+    let csv_type = csv.headers()["content-type"].to_str().unwrap().to_string();
+    let csv_body = csv.text().await.unwrap();
+    let json_body = get("/report/usage.json")
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    let unknown = get("/report/usage.csv?target=missing")
+        .await
+        .unwrap()
+        .status();
+    let disabled = get("/report/usage.json?target=no-snapshots")
+        .await
+        .unwrap()
+        .status();
+
+    // Then: Both formats list every group and unknown targets or disabled snapshots are rejected
+    assert!(csv_type.starts_with("text/csv"));
+    assert_eq!(
+        csv_body.lines().collect::<Vec<_>>(),
+        [
+            "datastore,namespace,owner,backup_type,backup_id,snapshot_count,logical_bytes,latest_snapshot_bytes,growth_bytes",
+            "backup,,tenant-b@pbs,ct,200,1,500,500,",
+            "backup,,tenant-a@pbs,vm,100,2,4000,3000,",
+        ]
+    );
+    assert!(json_body.contains(r#""owner":"tenant-a@pbs","backup_type":"vm","backup_id":"100","snapshot_count":2,"logical_bytes":4000"#));
+    assert!(json_body.contains(r#""growth_bytes":null"#));
+    assert!(!json_body.contains(r#""server""#));
+    assert_eq!(unknown, 404);
+    assert_eq!(disabled, 404);
+
+    server_handle.abort();
+}